@@ -0,0 +1,86 @@
+// Plays rounds between bots without a window and prints how they went, to see which rules
+// make rounds too long or favour the seat that starts.
+//
+//     cargo run --release --bin simulate -- --games 5000 --preset Party --bots NNHE --csv
+//
+// Rules are the defaults, a preset by name or a RON file of rules. Bots are seated anew
+// every round, so each level gets every seat as often
+
+use std::env;
+use std::process;
+use dos_game::simulation::{parse_options, simulate, Options, Stats, USAGE};
+
+fn main()
+{
+    let args: Vec<String> = env::args().skip(1).collect();
+    if args.iter().any(|arg| arg == "--help" || arg == "-h")
+    {
+        println!("{}", USAGE);
+        return;
+    }
+    let options = parse_options(args.into_iter()).unwrap_or_else(|err| {
+        eprintln!("{}\n\n{}", err, USAGE);
+        process::exit(2);
+    });
+    let stats = simulate(&options);
+
+    if options.csv { print_csv(&options, &stats) } else { print_table(&options, &stats) }
+    // Rounds a bot broke are told with their seeds and the run counts as failed
+    if !stats.failed.is_empty()
+    {
+        for failed in &stats.failed
+        {
+            eprintln!("Round {} with seed {} failed: {}", failed.game + 1, failed.seed, failed.error);
+        }
+        process::exit(1);
+    }
+}
+
+fn average(total: usize, games: usize) -> f64
+{
+    total as f64 / games.max(1) as f64
+}
+
+fn percent(count: usize, games: usize) -> f64
+{
+    100.0 * average(count, games)
+}
+
+fn print_table(options: &Options, stats: &Stats)
+{
+    let seats = options.bots.len();
+    println!("{} rounds of {} players, seed {}", stats.games, seats, options.seed);
+    println!();
+    println!("{:<10} {:>8} {:>8}", "Bot", "Wins", "Win %");
+    for (bot, level) in options.bots.iter().enumerate()
+    {
+        let name = format!("{} {:?}", bot + 1, level);
+        println!("{:<10} {:>8} {:>7.1}%", name, stats.wins[bot], percent(stats.wins[bot], stats.games));
+    }
+    println!();
+    println!("Average turns        {:>8.1}", average(stats.turns, stats.games));
+    println!("Average cards drawn  {:>8.1}", average(stats.cards_drawn, stats.games));
+    println!("First seat wins      {:>7.1}% (fair is {:.1}%)", percent(stats.first_seat_wins, stats.games), 100.0 / seats as f64);
+    println!("Unfinished rounds    {:>8}", stats.unfinished);
+    println!("Failed rounds        {:>8}", stats.failed.len());
+}
+
+fn print_csv(options: &Options, stats: &Stats)
+{
+    let bots: Vec<String> = options.bots.iter().enumerate().map(|(bot, level)| format!("bot{}_{:?}_win_rate", bot + 1, level)).collect();
+    println!("games,seed,players,avg_turns,avg_cards_drawn,first_seat_win_rate,unfinished,failed,{}", bots.join(","));
+
+    let rates: Vec<String> = stats.wins.iter().map(|&wins| format!("{:.4}", average(wins, stats.games))).collect();
+    println!(
+        "{},{},{},{:.2},{:.2},{:.4},{},{},{}",
+        stats.games,
+        options.seed,
+        options.bots.len(),
+        average(stats.turns, stats.games),
+        average(stats.cards_drawn, stats.games),
+        average(stats.first_seat_wins, stats.games),
+        stats.unfinished,
+        stats.failed.len(),
+        rates.join(","),
+    );
+}
@@ -0,0 +1,335 @@
+use rand::{rngs::StdRng, seq::SliceRandom, Rng, RngCore, SeedableRng};
+use serde::{Deserialize, Serialize};
+use crate::engine::{card_points, table_deck, Action, Card, GameEngine, Rank, Rules, Suit, COLORS};
+use crate::search::{SearchBot, SEARCH_BUDGET};
+
+// How often a Normal bot dares to challenge a Wild Draw 4
+const CHALLENGE_CHANCE: f64 = 0.3;
+// Seat this close to winning gets attacked with action cards
+const THREAT_HAND_SIZE: usize = 2;
+// Hard bots only challenge seats that likely kept a card of the old color
+const HARD_CHALLENGE_HAND_SIZE: usize = 5;
+
+// How well a bot plays, picked for every seat in the rules menu
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default, Serialize, Deserialize)]
+pub enum BotLevel
+{
+    // Any card that fits
+    Easy,
+    // Keeps wild cards and attacks a seat that is about to win
+    #[default]
+    Normal,
+    // Counts cards to switch to colors others are short of
+    Hard,
+    // Plays moves out on tables it imagines, see SearchBot
+    Master,
+}
+
+// What random numbers of a bot on a move are for, each gets numbers of its own
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum MoveChoice
+{
+    Action,
+    Call,
+}
+
+// What a seat can see of a table: its own hand, the pile, the direction and how many cards
+// everyone holds. Other hands and the deck stay hidden
+pub struct BotView<'a>
+{
+    engine: &'a GameEngine,
+    seat: usize,
+}
+
+// Decisions a bot makes on its turn. A whole turn is put together from them by `action`
+pub trait BotStrategy
+{
+    // Hand index of a card to play, out of `playable` ones that are never empty
+    fn pick_card(&self, view: &BotView, playable: &[usize], rng: &mut dyn RngCore) -> usize;
+
+    // Color a wild card is given
+    fn pick_color(&self, view: &BotView, rng: &mut dyn RngCore) -> Suit;
+
+    // Whether a Wild Draw 4 is challenged instead of drawing
+    fn challenge(&self, view: &BotView, rng: &mut dyn RngCore) -> bool;
+
+    // Seat to swap hands with after a Seven, the smallest hand by default
+    fn pick_swap(&self, view: &BotView, _rng: &mut dyn RngCore) -> usize
+    {
+        let sizes = view.hand_sizes();
+        (0..sizes.len())
+            .filter(|&other| other != view.seat)
+            .min_by_key(|&other| sizes[other])
+            .unwrap()
+    }
+
+    // Play a card that fits, otherwise draw (taking a stacked penalty, if any) until a pass
+    // is allowed. With no_skip a bot keeps drawing until something fits or there is nothing
+    // left to draw
+    fn action(&self, view: &BotView, rng: &mut dyn RngCore) -> Action
+    {
+        let (engine, seat) = (view.engine, view.seat);
+        if engine.choosing_color()
+        {
+            return Action::ChooseColor { seat, suit: self.pick_color(view, rng) };
+        }
+        if engine.choosing_swap()
+        {
+            return Action::SwapHands { seat, with: self.pick_swap(view, rng) };
+        }
+        if engine.rules().dos
+        {
+            return dos_action(engine, seat);
+        }
+        if engine.challenge_pending()
+        {
+            // Drawing is how a Wild Draw 4 is accepted
+            return if self.challenge(view, rng) { Action::Challenge { seat } } else { Action::Draw { seat } };
+        }
+
+        let playable = view.playable();
+        if !playable.is_empty()
+        {
+            return Action::Play { seat, index: self.pick_card(view, &playable, rng) };
+        }
+        if (!engine.has_drawn() || engine.rules().no_skip) && engine.can_draw()
+        {
+            return Action::Draw { seat };
+        }
+        Action::Pass { seat }
+    }
+}
+
+pub struct EasyBot;
+
+pub struct NormalBot;
+
+pub struct HardBot;
+
+impl BotLevel
+{
+    pub fn strategy(&self) -> &'static dyn BotStrategy
+    {
+        match self
+        {
+            BotLevel::Easy => &EasyBot,
+            BotLevel::Normal => &NormalBot,
+            BotLevel::Hard => &HardBot,
+            BotLevel::Master => &SearchBot { budget: SEARCH_BUDGET, rounds: None },
+        }
+    }
+}
+
+impl<'a> BotView<'a>
+{
+    pub fn new(engine: &'a GameEngine, seat: usize) -> Self
+    {
+        BotView { engine, seat }
+    }
+
+    pub fn seat(&self) -> usize { self.seat }
+
+    pub fn rules(&self) -> &'a Rules { self.engine.rules() }
+
+    pub fn hand(&self) -> &'a [Card] { self.engine.hand(self.seat) }
+
+    pub fn top_card(&self) -> &'a Card { self.engine.top_card() }
+
+    pub fn color(&self) -> Suit { self.engine.color() }
+
+    pub fn clockwise(&self) -> bool { self.engine.clockwise() }
+
+    pub fn discard_pile(&self) -> &'a [Card] { self.engine.discard_pile() }
+
+    pub fn hand_sizes(&self) -> Vec<usize>
+    {
+        (0..self.engine.num_seats()).map(|seat| self.engine.hand(seat).len()).collect()
+    }
+
+    // Seat that plays after this one
+    pub fn next_seat(&self) -> usize { self.engine.next_seat(self.seat) }
+
+    // Seat that played before this one
+    pub fn previous_seat(&self) -> usize
+    {
+        (0..self.engine.num_seats()).find(|&seat| self.engine.next_seat(seat) == self.seat).unwrap_or(self.seat)
+    }
+
+    // Hand indices of cards that can go on the pile right now
+    pub fn playable(&self) -> Vec<usize>
+    {
+        let hand = self.hand();
+        let first = if self.engine.only_drawn_playable() { hand.len().saturating_sub(1) } else { 0 };
+        (first..hand.len()).filter(|&index| self.engine.can_play(&hand[index])).collect()
+    }
+
+    // Every action the rules allow this seat right now
+    pub fn legal_actions(&self) -> Vec<Action>
+    {
+        let (engine, seat) = (self.engine, self.seat);
+        let hand_size = self.hand().len();
+        let mut actions = vec![Action::Draw { seat }, Action::Pass { seat }, Action::Challenge { seat }];
+        actions.extend((0..hand_size).map(|index| Action::Play { seat, index }));
+        actions.extend(COLORS.iter().map(|&suit| Action::ChooseColor { seat, suit }));
+        actions.extend((0..engine.num_seats()).map(|with| Action::SwapHands { seat, with }));
+        actions.extend(engine.matches(seat).into_iter().map(|(row, first, second)| Action::Match { seat, row, first, second }));
+        actions.extend((0..hand_size).map(|index| Action::AddToRow { seat, index }));
+        actions.retain(|&action| engine.clone().apply(action).is_ok());
+        actions
+    }
+
+    // Table as this seat could imagine it, with hidden cards dealt at random
+    pub fn imagine(&self, rng: &mut dyn RngCore) -> GameEngine
+    {
+        self.engine.determinize(self.seat, rng)
+    }
+
+    // Cards of a color that are in the deck or other hands, as far as this seat can tell
+    pub fn unseen(&self, color: Suit) -> usize
+    {
+        let seen = self.hand().iter()
+            .chain(self.discard_pile())
+            .chain(self.engine.centre_row())
+            .filter(|card| card.suite == color)
+            .count();
+        let total = table_deck(self.rules()).iter().filter(|card| card.suite == color).count();
+        total.saturating_sub(seen)
+    }
+}
+
+impl BotStrategy for EasyBot
+{
+    fn pick_card(&self, _view: &BotView, playable: &[usize], rng: &mut dyn RngCore) -> usize
+    {
+        *playable.choose(rng).unwrap()
+    }
+
+    fn pick_color(&self, _view: &BotView, rng: &mut dyn RngCore) -> Suit
+    {
+        *COLORS.choose(rng).unwrap()
+    }
+
+    fn pick_swap(&self, view: &BotView, rng: &mut dyn RngCore) -> usize
+    {
+        let others: Vec<usize> = (0..view.hand_sizes().len()).filter(|&other| other != view.seat).collect();
+        *others.choose(rng).unwrap()
+    }
+
+    fn challenge(&self, _view: &BotView, rng: &mut dyn RngCore) -> bool
+    {
+        rng.gen_bool(0.5)
+    }
+}
+
+impl BotStrategy for NormalBot
+{
+    // Wild cards are kept for when nothing else fits. A seat about to win gets action cards,
+    // otherwise the most points are shed first
+    fn pick_card(&self, view: &BotView, playable: &[usize], _rng: &mut dyn RngCore) -> usize
+    {
+        let hand = view.hand();
+        let threat = view.hand_sizes()[view.next_seat()] <= THREAT_HAND_SIZE;
+        *playable.iter()
+            .max_by_key(|&&index| {
+                let card = &hand[index];
+                (threat && is_attack(card), card.suite != Suit::Black, card_points(card))
+            })
+            .unwrap()
+    }
+
+    fn pick_color(&self, view: &BotView, _rng: &mut dyn RngCore) -> Suit
+    {
+        favourite_color(view.hand())
+    }
+
+    fn challenge(&self, _view: &BotView, rng: &mut dyn RngCore) -> bool
+    {
+        rng.gen_bool(CHALLENGE_CHANCE)
+    }
+}
+
+impl BotStrategy for HardBot
+{
+    // Color a card leaves on the pile is worth more the more of it the bot keeps and the fewer
+    // of it are left for others. Wild cards are saved until the hand is almost empty
+    fn pick_card(&self, view: &BotView, playable: &[usize], rng: &mut dyn RngCore) -> usize
+    {
+        let hand = view.hand();
+        let threat = view.hand_sizes()[view.next_seat()] <= THREAT_HAND_SIZE;
+        let save_wilds = hand.len() > THREAT_HAND_SIZE + 1;
+        *playable.iter()
+            .max_by_key(|&&index| {
+                let card = &hand[index];
+                let color = if card.suite == Suit::Black { self.pick_color(view, rng) } else { card.suite };
+                let kept = hand.iter().enumerate().filter(|&(other, held)| other != index && held.suite == color).count();
+                let worth = kept as i64 * 4 - view.unseen(color) as i64;
+                (threat && is_attack(card), !(save_wilds && card.suite == Suit::Black), worth, card_points(card))
+            })
+            .unwrap()
+    }
+
+    // Most cards in hand, and of those the color others hold fewest of
+    fn pick_color(&self, view: &BotView, _rng: &mut dyn RngCore) -> Suit
+    {
+        let hand = view.hand();
+        *COLORS.iter()
+            .max_by_key(|&&color| {
+                let held = hand.iter().filter(|card| card.suite == color).count();
+                (held, std::cmp::Reverse(view.unseen(color)))
+            })
+            .unwrap()
+    }
+
+    // Big hands are likely to have had a card of the old color
+    fn challenge(&self, view: &BotView, _rng: &mut dyn RngCore) -> bool
+    {
+        view.hand_sizes()[view.previous_seat()] >= HARD_CHALLENGE_HAND_SIZE
+    }
+}
+
+// Cards that hold back the next seat
+fn is_attack(card: &Card) -> bool
+{
+    matches!(card.rank, Rank::Skip | Rank::Reverse | Rank::Draw2 | Rank::WildDraw4)
+}
+
+// Random numbers of a bot follow from the seed of a round and the number of the move, so
+// that a seed plays out the same way however frames are timed
+pub fn move_rng(seed: u64, moves: usize, choice: MoveChoice) -> StdRng
+{
+    let moves = (moves as u64) << 1 | choice as u64;
+    StdRng::seed_from_u64(seed ^ moves.wrapping_mul(0x9E37_79B9_7F4A_7C15))
+}
+
+// Color a hand has the most cards of
+pub fn favourite_color(hand: &[Card]) -> Suit
+{
+    *COLORS.iter()
+        .max_by_key(|&&color| hand.iter().filter(|card| card.suite == color).count())
+        .unwrap()
+}
+
+// A bot takes off as many cards as it can, color matches first. Without a match it draws,
+// and puts its most valuable card on the centre row if that didn't help
+fn dos_action(engine: &GameEngine, seat: usize) -> Action
+{
+    let hand = engine.hand(seat);
+    let most_valuable = || (0..hand.len()).max_by_key(|&index| card_points(&hand[index])).unwrap();
+    if engine.owed_to_row() > 0
+    {
+        return Action::AddToRow { seat, index: most_valuable() };
+    }
+
+    let best = engine.matches(seat).into_iter().max_by_key(|&(row, first, second)| {
+        let target = engine.centre_row()[row];
+        let color_match = target.suite != Suit::Black && std::iter::once(first).chain(second).all(|index| hand[index].suite == target.suite);
+        (color_match, second.is_some())
+    });
+    match best
+    {
+        Some((row, first, second)) => Action::Match { seat, row, first, second },
+        None if engine.has_matched() => Action::Pass { seat },
+        None if !engine.has_drawn() && engine.can_draw() => Action::Draw { seat },
+        None => Action::AddToRow { seat, index: most_valuable() },
+    }
+}
@@ -0,0 +1,1277 @@
+use std::fmt;                // Conver enum variants into string
+use rand::{rngs::StdRng, seq::SliceRandom, Rng, SeedableRng};
+use num_derive::FromPrimitive;  //derive a trait on enum to access it with integer
+use num::FromPrimitive;         //access enum values via integer
+use serde::{Deserialize, Serialize};
+use crate::bots::BotLevel;
+
+//----------------------------------------------------------------------------------
+//  Game configurations
+//----------------------------------------------------------------------------------
+
+// Defaults of Rules
+pub const HAND_SIZE: usize = 7;
+pub const RANK_COPIES: usize = 2;
+pub const TARGET_SCORE: usize = 500;
+pub const CATCH_DELAY: usize = 1500;
+pub const TURN_TIME: usize = 10;
+// Seats a table has room for
+pub const MAX_PLAYERS: usize = 12;
+// How many Wild and Wild Draw 4 cards a deck has
+const WILD_COPIES: usize = 4;
+// Face-up cards the centre row is topped up to after every turn in DOS mode
+pub const CENTRE_ROW_SIZE: usize = 2;
+// Cards drawn by a seat caught without calling its last card
+pub const CATCH_PENALTY: usize = 2;
+// Lowest cap of a stacked penalty, so a single Wild Draw 4 is never cut short
+pub const MIN_PENALTY_CAP: usize = 4;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, FromPrimitive)]
+pub enum Rank
+{
+    Zero,
+    One,
+    Two,
+    Three,
+    Four,
+    Five,
+    Six,
+    Seven,
+    Eight,
+    Nine,
+    Skip,
+    Reverse,
+    Draw2,
+    Wild,
+    WildDraw4
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, FromPrimitive, Serialize, Deserialize)]
+pub enum Suit
+{
+    Red,
+    Blue,
+    Yellow,
+    Green,
+    // Wild cards don't have a color until the one who plays them picks it
+    Black,
+}
+
+// Colors that can be matched and picked after playing a wild card
+pub const COLORS: [Suit; 4] = [Suit::Red, Suit::Blue, Suit::Yellow, Suit::Green];
+
+// Once a seat has drawn a card, it may go on to play
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub enum AfterDraw
+{
+    // Any card that fits
+    AnyCard,
+    // Only the drawn card, or the turn is passed
+    OnlyDrawn,
+    // Only the drawn card, and it has to be played if it fits
+    MustPlayDrawn,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Card
+{
+    pub rank: Rank,
+    pub suite: Suit,
+    pub id: usize,
+}
+
+// Rules missing from a saved file keep their defaults
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Rules
+{
+    pub num_players: usize,
+    // Draw2 can be answered with another Draw2, passing on a growing penalty
+    pub stackable_cards: bool,
+    // While stacking, a Wild Draw 4 can be put on a Draw2 too
+    pub stack_draw4: bool,
+    // Draw2 and Wild Draw 4 go on each other in any order, adding up to one penalty. A Draw2
+    // on a Wild Draw 4 has to be of the chosen color
+    pub progressive: bool,
+    // Most cards a stacked penalty grows to, 0 for no limit
+    pub penalty_cap: usize,
+    // Skip of the current color passes a stacked penalty on to the next seat, Reverse sends
+    // it back the other way
+    pub deflect_penalty: bool,
+    // Every turn has `turn_time` seconds, after which a seat draws a card and the turn passes
+    pub turbo: bool,
+    pub turn_time: usize,
+    pub clockwise: bool,
+    pub no_skip: bool,
+    // Drawing goes on by itself until a card that fits turns up or nothing is left to draw
+    pub draw_until_playable: bool,
+    // Which cards can be played after drawing one
+    pub after_draw: AfterDraw,
+    pub wild_draw4_challenge: bool,
+    // Seven swaps hands with a chosen seat, Zero passes every hand on in the current direction
+    pub seven_o: bool,
+    // A card identical to the top one can be played out of turn, the turn goes on from there
+    pub jump_in: bool,
+    // Rounds are played until somebody has this many points
+    pub target_score: usize,
+    pub hand_size: usize,
+    // Copies of every rank of every suit. Zeros come in half as many
+    pub rank_copies: usize,
+    // Skip, Reverse and Draw2
+    pub action_cards: bool,
+    // Wild and Wild Draw 4
+    pub wild_cards: bool,
+    // Decks shuffled together. More are added for tables that need them
+    pub deck_multiplier: usize,
+    // Number cards are matched against a centre row instead of a discard pile, one card by
+    // its number or two cards adding up to it. Only number cards and plain wilds are dealt
+    pub dos: bool,
+    // Seat down to its last card, or last two in DOS mode, has to call it before the next
+    // seat acts, or it can be caught and made to draw
+    pub last_call: bool,
+    // Milliseconds bots take to catch a missed call
+    pub catch_delay: usize,
+    // How well a bot at every seat plays. The one of the main player's seat is not used
+    pub bot_levels: [BotLevel; MAX_PLAYERS],
+}
+
+//----------------------------------------------------------------------------------
+//  Actions, Events and Errors
+//----------------------------------------------------------------------------------
+
+// Everything a seat can do. Seat 0 is always the main player
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub enum Action
+{
+    Draw { seat: usize },
+    Play { seat: usize, index: usize },
+    // End a turn after drawing a card
+    Pass { seat: usize },
+    // Required right after playing a wild card
+    ChooseColor { seat: usize, suit: Suit },
+    // Answer to a Wild Draw 4 instead of drawing, when challenges are allowed
+    Challenge { seat: usize },
+    // Required right after playing a Seven with the Seven-O rule
+    SwapHands { seat: usize, with: usize },
+    // DOS mode: one card of a hand with the number of a centre row card, or two adding up to it
+    Match { seat: usize, row: usize, first: usize, second: Option<usize> },
+    // DOS mode: a card goes on the centre row, for a color match or when nothing was matched
+    AddToRow { seat: usize, index: usize },
+    // Uno, or DOS in DOS mode, right after getting down to the last cards
+    Call { seat: usize },
+    // Any other seat can catch a missed call, making `target` draw
+    Catch { seat: usize, target: usize },
+    // Turbo mode turn ran out. Whatever was left to pick is given up, a card is drawn and
+    // the turn passes
+    TimeOut { seat: usize },
+}
+
+// What happened after an action was applied, in order
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Event
+{
+    CardDrawn { seat: usize, card: Card },
+    CardPlayed { seat: usize, card: Card },
+    // Seat took the turn to play an identical card, comes right before its CardPlayed
+    JumpedIn { seat: usize },
+    ColorChosen { seat: usize, suit: Suit },
+    // `bluffed` is true when the Wild Draw 4 was played while holding a card of the current color
+    Challenged { seat: usize, bluffed: bool },
+    DirectionChanged { clockwise: bool },
+    HandsSwapped { seat: usize, with: usize },
+    // Every hand went to the next seat in the current direction
+    HandsRotated { clockwise: bool },
+    TurnSkipped { seat: usize },
+    TurnChanged { seat: usize },
+    // Deck ran out and `count` cards of a discard pile were shuffled into it
+    DeckReshuffled { count: usize },
+    // Seat has played its last card, no more actions are accepted
+    RoundWon { seat: usize },
+    // Centre row card went to the discard pile with the cards matched to it.
+    // `color_match` is true when all of them have its color
+    Matched { seat: usize, target: Card, first: Card, second: Option<Card>, color_match: bool },
+    AddedToRow { seat: usize, card: Card },
+    // Centre row was topped up from a deck at the end of a turn
+    RowRefilled { card: Card },
+    // Seat called its last card, or last two in DOS mode
+    Called { seat: usize },
+    // Seat caught `target` without a call, CardDrawn of the penalty follow
+    Caught { seat: usize, target: usize },
+    // Comes first among the events of a turn that ran out
+    TimedOut { seat: usize },
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum RuleError
+{
+    NotEnoughCards,
+    NotYourTurn,
+    NoSuchCard,
+    CardDoesNotMatch,
+    AlreadyDrawn,
+    MustDrawFirst,
+    PassNotAllowed,
+    DeckEmpty,
+    MustChooseColor,
+    NoColorToChoose,
+    NotAColor,
+    MustAnswerChallenge,
+    NothingToChallenge,
+    MustStackOrDraw,
+    RoundOver,
+    NoPlayers,
+    TooManyPlayers,
+    EmptyHands,
+    NoTurnTime,
+    PenaltyCapTooLow,
+    MustPickSwap,
+    NoSwapToPick,
+    NoSuchSeat,
+    MustPlayDrawn,
+    OnlyDrawnCard,
+    NotDosMode,
+    MatchInDosMode,
+    NoSuchRowCard,
+    NoMatch,
+    AlreadyMatched,
+    MustAddToRow,
+    NothingToAdd,
+    NothingToCall,
+    NothingToCatch,
+    NotTurbo,
+}
+
+//----------------------------------------------------------------------------------
+//  Engine
+//----------------------------------------------------------------------------------
+
+#[derive(Debug, Clone)]
+pub struct GameEngine
+{
+    rules: Rules,
+    deck: Vec<Card>,
+    discard_pile: Vec<Card>,
+    hands: Vec<Vec<Card>>,
+    turn: usize,
+    // Turns started since the deal, counting every one a seat keeps or gets back
+    turns: usize,
+    clockwise: bool,
+    drawn_card: bool,
+    // Color that has to be matched. Differs from the top card only after a wild card
+    color: Suit,
+    // A wild card was played and the current seat still has to pick a color
+    choosing_color: bool,
+    // A Seven was played and the current seat still has to pick whom to swap hands with
+    choosing_swap: bool,
+    challenge: Option<Challenge>,
+    // Cards the current seat has to draw unless it stacks another draw card
+    penalty: usize,
+    // DOS mode cards to match against, left to right
+    centre_row: Vec<Card>,
+    // Current seat matched at least one centre row card this turn
+    matched: bool,
+    // Cards the current seat still has to put on the centre row for its color matches
+    owed_to_row: usize,
+    // Seat that got down to its last cards without calling them, while it can be caught
+    uncalled: Option<usize>,
+    // Used to reshuffle a discard pile into an empty deck
+    rng: StdRng,
+    // Every action applied since the deal, in order
+    history: Vec<Action>,
+}
+
+// Wild Draw 4 that the current seat may challenge
+#[derive(Debug, Clone, Copy)]
+struct Challenge
+{
+    by: usize,
+    // Color the card was played on, holding a card of it is a bluff
+    color: Suit,
+    bluffed: bool,
+}
+
+impl GameEngine
+{
+    // Build a deck big enough for a table, shuffle it and deal a hand to every seat
+    pub fn new<R: Rng + ?Sized>(rules: Rules, rng: &mut R) -> Result<Self, RuleError>
+    {
+        rules.validate()?;
+        let mut deck = table_deck(&rules);
+        deck.shuffle(rng);
+        let mut engine = Self::deal(rules, deck)?;
+        engine.rng = StdRng::seed_from_u64(rng.gen());
+        Ok(engine)
+    }
+
+    // Deal hands from the front of a deck and flip its last colored card onto the discard pile
+    pub fn deal(rules: Rules, mut deck: Vec<Card>) -> Result<Self, RuleError>
+    {
+        if rules.num_players == 0 || rules.num_players * rules.hand_size >= deck.len()
+        {
+            return Err(RuleError::NotEnoughCards);
+        }
+
+        let hands = (0..rules.num_players).map(|_| deck.drain(..rules.hand_size).collect()).collect();
+        let top = deck.iter().rposition(|card| card.suite != Suit::Black).ok_or(RuleError::NotEnoughCards)?;
+        let discard_pile = vec![deck.remove(top)];
+        if rules.dos && deck.len() < CENTRE_ROW_SIZE { return Err(RuleError::NotEnoughCards); }
+        Ok(Self::with_cards(rules, hands, discard_pile, deck))
+    }
+
+    // Start from an exact table layout. The last card of `discard_pile` is the top one,
+    // the last card of `deck` is drawn first. Reshuffles are the same every time.
+    // In DOS mode a centre row is laid out from the deck
+    pub fn with_cards(rules: Rules, hands: Vec<Vec<Card>>, discard_pile: Vec<Card>, deck: Vec<Card>) -> Self
+    {
+        let mut engine = GameEngine {
+            rules,
+            deck,
+            hands,
+            turn: 0,
+            turns: 0,
+            clockwise: rules.clockwise,
+            drawn_card: false,
+            color: discard_pile.last().unwrap().suite,
+            choosing_color: false,
+            choosing_swap: false,
+            challenge: None,
+            penalty: 0,
+            centre_row: vec![],
+            matched: false,
+            owed_to_row: 0,
+            uncalled: None,
+            rng: StdRng::seed_from_u64(0),
+            history: vec![],
+            discard_pile,
+        };
+        if rules.dos { engine.refill_row(); }
+        engine
+    }
+
+    pub fn rules(&self) -> &Rules { &self.rules }
+
+    pub fn turn(&self) -> usize { self.turn }
+
+    pub fn turns(&self) -> usize { self.turns }
+
+    pub fn clockwise(&self) -> bool { self.clockwise }
+
+    pub fn has_drawn(&self) -> bool { self.drawn_card }
+
+    // Cards are drawn to the end of a hand, so the last one is what the current seat drew
+    pub fn drawn(&self) -> Option<&Card>
+    {
+        if self.drawn_card { self.hands[self.turn].last() } else { None }
+    }
+
+    // Whether the rules leave nothing but the drawn card to play
+    pub fn only_drawn_playable(&self) -> bool
+    {
+        self.drawn_card && self.rules.after_draw != AfterDraw::AnyCard
+    }
+
+    pub fn num_seats(&self) -> usize { self.hands.len() }
+
+    pub fn hand(&self, seat: usize) -> &[Card] { &self.hands[seat] }
+
+    pub fn deck(&self) -> &[Card] { &self.deck }
+
+    pub fn history(&self) -> &[Action] { &self.history }
+
+    pub fn discard_pile(&self) -> &[Card] { &self.discard_pile }
+
+    pub fn top_card(&self) -> &Card { self.discard_pile.last().unwrap() }
+
+    pub fn color(&self) -> Suit { self.color }
+
+    pub fn choosing_color(&self) -> bool { self.choosing_color }
+
+    pub fn choosing_swap(&self) -> bool { self.choosing_swap }
+
+    pub fn challenge_pending(&self) -> bool { self.challenge.is_some() }
+
+    pub fn penalty(&self) -> usize { self.penalty }
+
+    pub fn centre_row(&self) -> &[Card] { &self.centre_row }
+
+    pub fn has_matched(&self) -> bool { self.matched }
+
+    pub fn owed_to_row(&self) -> usize { self.owed_to_row }
+
+    pub fn uncalled(&self) -> Option<usize> { self.uncalled }
+
+    // Cards left in a hand that have to be called
+    pub fn last_cards(&self) -> usize { if self.rules.dos { 2 } else { 1 } }
+
+    // False only when both a deck and a discard pile under its top card are empty
+    pub fn can_draw(&self) -> bool { !self.deck.is_empty() || self.discard_pile.len() > 1 }
+
+    // Seat that ran out of cards first, which ends a round
+    pub fn winner(&self) -> Option<usize> { self.hands.iter().position(|hand| hand.is_empty()) }
+
+    // What a winner gets for all the cards left in other hands
+    pub fn round_points(&self) -> usize
+    {
+        self.hands.iter().flatten().map(card_points).sum()
+    }
+
+    // Out of turn only a colored card of the same suit and rank as the top one can be played,
+    // and only while nothing is waiting to be answered
+    pub fn can_jump_in(&self, card: &Card) -> bool
+    {
+        let top = self.top_card();
+        self.rules.jump_in && !self.rules.dos && self.penalty == 0 && self.check_nothing_pending().is_ok()
+            && card.suite != Suit::Black && card.suite == top.suite && card.rank == top.rank
+    }
+
+    // A card can go on the pile if it shares a rank with the top card or has the current color.
+    // Wild cards go on anything. While a penalty is pending only draw cards can be stacked,
+    // or a Skip and Reverse played to deflect it
+    pub fn can_play(&self, card: &Card) -> bool
+    {
+        let top = self.top_card();
+        if self.penalty > 0
+        {
+            return match card.rank
+            {
+                Rank::Draw2 => top.rank == Rank::Draw2 || (card.suite == self.color && (self.rules.progressive || top.suite != Suit::Black)),
+                Rank::WildDraw4 => self.rules.stack_draw4 || self.rules.progressive,
+                Rank::Skip | Rank::Reverse => self.rules.deflect_penalty && (card.suite == self.color || card.rank == top.rank),
+                _ => false,
+            };
+        }
+        card.suite == Suit::Black || card.suite == self.color || (top.suite != Suit::Black && card.rank == top.rank)
+    }
+
+    // Every match a seat could make in DOS mode, as centre row index and one or two hand indices
+    pub fn matches(&self, seat: usize) -> Vec<(usize, usize, Option<usize>)>
+    {
+        let hand = &self.hands[seat];
+        let mut matches = vec![];
+        for (row, target) in self.centre_row.iter().enumerate()
+        {
+            for first in 0..hand.len()
+            {
+                if dos_match(target, &[hand[first]]) { matches.push((row, first, None)); }
+                for second in first + 1..hand.len()
+                {
+                    if dos_match(target, &[hand[first], hand[second]]) { matches.push((row, first, Some(second))); }
+                }
+            }
+        }
+        matches
+    }
+
+    // Seat that plays after `seat` in the current direction
+    pub fn next_seat(&self, seat: usize) -> usize
+    {
+        let seats = self.hands.len();
+        if self.clockwise { (seat + seats - 1) % seats } else { (seat + 1) % seats }
+    }
+
+    // Same table as a seat could imagine it: cards it can't see, in other hands and the deck,
+    // are dealt again at random. Hands keep their sizes and the deck its length. Every hidden
+    // card is as likely anywhere, even where play so far tells it can't be, e.g. a seat that
+    // drew instead of playing a color may still be dealt cards of it
+    pub fn determinize<R: Rng + ?Sized>(&self, seat: usize, rng: &mut R) -> GameEngine
+    {
+        let mut table = self.clone();
+        let mut hidden = std::mem::take(&mut table.deck);
+        for (other, hand) in table.hands.iter().enumerate()
+        {
+            if other != seat { hidden.extend(hand); }
+        }
+        hidden.shuffle(rng);
+        for other in 0..table.hands.len()
+        {
+            if other == seat { continue; }
+            let size = table.hands[other].len();
+            table.hands[other] = hidden.split_off(hidden.len() - size);
+        }
+        table.deck = hidden;
+        table.rng = StdRng::seed_from_u64(rng.gen());
+        // Whether a pending Wild Draw 4 was a bluff follows the hand it was played from
+        if let Some(challenge) = table.challenge.as_mut()
+        {
+            challenge.bluffed = table.hands[challenge.by].iter().any(|held| held.suite == challenge.color);
+        }
+        table
+    }
+
+    // Actions are kept once applied, with the seed of a deal they replay a whole round
+    pub fn apply(&mut self, action: Action) -> Result<Vec<Event>, RuleError>
+    {
+        let events = self.act(action)?;
+        self.history.push(action);
+        Ok(events)
+    }
+
+    fn act(&mut self, action: Action) -> Result<Vec<Event>, RuleError>
+    {
+        if self.winner().is_some() { return Err(RuleError::RoundOver); }
+
+        let seat = action.seat();
+        let cards_before = self.hands.get(seat).map(Vec::len);
+        let events = match action
+        {
+            Action::Draw { seat } => self.draw(seat),
+            Action::Play { seat, index } => self.play(seat, index),
+            Action::Pass { seat } => self.pass(seat),
+            Action::ChooseColor { seat, suit } => self.choose_color(seat, suit),
+            Action::Challenge { seat } => self.answer_challenge(seat),
+            Action::SwapHands { seat, with } => self.swap_hands(seat, with),
+            Action::Match { seat, row, first, second } => self.match_row(seat, row, first, second),
+            Action::AddToRow { seat, index } => self.add_to_row(seat, index),
+            Action::Call { seat } => return self.call(seat),
+            Action::Catch { seat, target } => return self.catch(seat, target),
+            Action::TimeOut { seat } => self.time_out(seat),
+        }?;
+
+        // A missed call can be caught only until another seat acts, or the seat itself
+        // gets more or fewer cards
+        if self.uncalled.is_some_and(|uncalled| uncalled != seat || self.hands[uncalled].len() != self.last_cards())
+        {
+            self.uncalled = None;
+        }
+        if self.rules.last_call && self.hands[seat].len() == self.last_cards() && cards_before != Some(self.last_cards())
+        {
+            self.uncalled = Some(seat);
+        }
+        Ok(events)
+    }
+
+    fn draw(&mut self, seat: usize) -> Result<Vec<Event>, RuleError>
+    {
+        self.check_turn(seat)?;
+        self.check_no_choice()?;
+        // Drawing is how a penalty is accepted, without stacking or a challenge
+        if self.penalty > 0
+        {
+            self.challenge = None;
+            let count = std::mem::take(&mut self.penalty);
+            let mut events = self.give_cards(seat, count);
+            events.push(Event::TurnSkipped { seat });
+            events.push(self.end_turn());
+            return Ok(events);
+        }
+        if self.matched { return Err(RuleError::AlreadyMatched); }
+        if !self.rules.no_skip && self.drawn_card { return Err(RuleError::AlreadyDrawn); }
+
+        let mut events = vec![];
+        let card = self.take_card(&mut events).ok_or(RuleError::DeckEmpty)?;
+        self.hands[seat].push(card);
+        self.drawn_card = true;
+        events.push(Event::CardDrawn { seat, card });
+        if self.rules.draw_until_playable
+        {
+            let mut card = card;
+            while !self.drawn_card_fits(seat, &card)
+            {
+                let Some(next) = self.take_card(&mut events) else { break; };
+                self.hands[seat].push(next);
+                events.push(Event::CardDrawn { seat, card: next });
+                card = next;
+            }
+        }
+        Ok(events)
+    }
+
+    fn play(&mut self, seat: usize, index: usize) -> Result<Vec<Event>, RuleError>
+    {
+        if self.rules.dos { return Err(RuleError::MatchInDosMode); }
+        let mut events = vec![];
+        if seat != self.turn && seat < self.hands.len() && self.rules.jump_in
+        {
+            let card = self.hands[seat].get(index).ok_or(RuleError::NoSuchCard)?;
+            if !self.can_jump_in(card) { return Err(RuleError::NotYourTurn); }
+            self.turn = seat;
+            self.turns += 1;
+            self.drawn_card = false;
+            events.push(Event::JumpedIn { seat });
+        }
+
+        self.check_turn(seat)?;
+        self.check_nothing_pending()?;
+        let card = *self.hands[seat].get(index).ok_or(RuleError::NoSuchCard)?;
+        if self.only_drawn_playable() && index + 1 != self.hands[seat].len()
+        {
+            return Err(RuleError::OnlyDrawnCard);
+        }
+        if !self.can_play(&card)
+        {
+            let err = if self.penalty > 0 { RuleError::MustStackOrDraw } else { RuleError::CardDoesNotMatch };
+            return Err(err);
+        }
+
+        self.hands[seat].remove(index);
+        self.discard_pile.push(card);
+        self.drawn_card = false;
+
+        events.push(Event::CardPlayed { seat, card });
+        // Last card ends a round right away, its effects and a color don't matter anymore
+        if self.hands[seat].is_empty()
+        {
+            self.challenge = None;
+            self.penalty = 0;
+            events.push(Event::RoundWon { seat });
+            return Ok(events);
+        }
+        // Turn waits for a color to be picked
+        if card.suite == Suit::Black
+        {
+            // A stacked Wild Draw 4 can't be challenged, the Draw2 under it made it legal
+            if card.rank == Rank::WildDraw4 && self.rules.wild_draw4_challenge && self.penalty == 0
+            {
+                let bluffed = self.hands[seat].iter().any(|held| held.suite == self.color);
+                self.challenge = Some(Challenge { by: seat, color: self.color, bluffed });
+            }
+            self.choosing_color = true;
+            return Ok(events);
+        }
+
+        self.color = card.suite;
+        // Turn waits for a seat to swap hands with
+        if card.rank == Rank::Seven && self.rules.seven_o && self.hands.len() > 1
+        {
+            self.choosing_swap = true;
+            return Ok(events);
+        }
+        events.extend(self.resolve(card));
+        Ok(events)
+    }
+
+    fn swap_hands(&mut self, seat: usize, with: usize) -> Result<Vec<Event>, RuleError>
+    {
+        self.check_turn(seat)?;
+        if !self.choosing_swap { return Err(RuleError::NoSwapToPick); }
+        if with == seat || with >= self.hands.len() { return Err(RuleError::NoSuchSeat); }
+
+        self.choosing_swap = false;
+        self.hands.swap(seat, with);
+        let mut events = vec![Event::HandsSwapped { seat, with }];
+        events.extend(self.resolve(*self.top_card()));
+        Ok(events)
+    }
+
+    // Every hand goes to the seat that plays after its owner
+    fn rotate_hands(&mut self) -> Event
+    {
+        let mut hands = vec![Vec::new(); self.hands.len()];
+        for seat in 0..self.hands.len()
+        {
+            hands[self.next_seat(seat)] = std::mem::take(&mut self.hands[seat]);
+        }
+        self.hands = hands;
+        Event::HandsRotated { clockwise: self.clockwise }
+    }
+
+    fn choose_color(&mut self, seat: usize, suit: Suit) -> Result<Vec<Event>, RuleError>
+    {
+        self.check_turn(seat)?;
+        if !self.choosing_color { return Err(RuleError::NoColorToChoose); }
+        if suit == Suit::Black { return Err(RuleError::NotAColor); }
+
+        self.choosing_color = false;
+        self.color = suit;
+        let mut events = vec![Event::ColorChosen { seat, suit }];
+        events.extend(self.resolve(*self.top_card()));
+        Ok(events)
+    }
+
+    fn answer_challenge(&mut self, seat: usize) -> Result<Vec<Event>, RuleError>
+    {
+        self.check_turn(seat)?;
+        self.check_no_choice()?;
+        let challenge = self.challenge.take().ok_or(RuleError::NothingToChallenge)?;
+
+        let mut events = vec![Event::Challenged { seat, bluffed: challenge.bluffed }];
+        let count = std::mem::take(&mut self.penalty);
+        if challenge.bluffed
+        {
+            // Caught: the one who played it draws instead and the challenger plays as usual
+            events.extend(self.give_cards(challenge.by, count));
+        }
+        else
+        {
+            events.extend(self.give_cards(seat, count + 2));
+            events.push(Event::TurnSkipped { seat });
+            events.push(self.end_turn());
+        }
+        Ok(events)
+    }
+
+    // Effects of a card that was just put on the pile, including passing a turn
+    fn resolve(&mut self, card: Card) -> Vec<Event>
+    {
+        let mut events = vec![];
+        if card.rank == Rank::Reverse
+        {
+            self.clockwise = !self.clockwise;
+            events.push(Event::DirectionChanged { clockwise: self.clockwise });
+        }
+        // In case only 1 player in a lobby, don't pass a turn
+        if self.hands.len() == 1
+        {
+            self.challenge = None;
+            self.penalty = 0;
+            return events;
+        }
+
+        let stacking = self.rules.stackable_cards || self.rules.progressive;
+        match card.rank
+        {
+            // Deflected penalty goes to the seat that would have been skipped
+            Rank::Skip if self.penalty > 0 => (),
+            Rank::Skip => events.push(self.skip_next()),
+            // With two players a Reverse gives the turn straight back, same as a Skip, unless
+            // it sends a penalty back
+            Rank::Reverse if self.hands.len() == 2 && self.penalty == 0 => events.push(self.skip_next()),
+            Rank::Zero if self.rules.seven_o => events.push(self.rotate_hands()),
+            // Next seat gets the turn to either stack or draw everything
+            Rank::Draw2 if stacking => self.add_penalty(2),
+            Rank::WildDraw4 if stacking || self.challenge.is_some() => self.add_penalty(4),
+            Rank::Draw2 => {
+                let victim = self.next_seat(self.turn);
+                events.extend(self.give_cards(victim, 2));
+                events.push(self.skip_next());
+            },
+            Rank::WildDraw4 => {
+                let victim = self.next_seat(self.turn);
+                events.extend(self.give_cards(victim, 4));
+                events.push(self.skip_next());
+            },
+            _ => (),
+        }
+
+        events.push(self.end_turn());
+        events
+    }
+
+    fn add_penalty(&mut self, count: usize)
+    {
+        self.penalty += count;
+        if self.rules.penalty_cap > 0
+        {
+            self.penalty = self.penalty.min(self.rules.penalty_cap);
+        }
+    }
+
+    fn pass(&mut self, seat: usize) -> Result<Vec<Event>, RuleError>
+    {
+        self.check_turn(seat)?;
+        self.check_nothing_pending()?;
+        if self.rules.dos
+        {
+            // Without a match a turn ends by putting a card on the centre row
+            if self.owed_to_row > 0 { return Err(RuleError::MustAddToRow); }
+            if !self.matched
+            {
+                let err = if self.can_draw() && !self.drawn_card { RuleError::MustDrawFirst } else { RuleError::MustAddToRow };
+                return Err(err);
+            }
+            return Ok(self.end_dos_turn());
+        }
+        // Nobody can be made to draw from nothing
+        if self.can_draw()
+        {
+            if self.rules.no_skip { return Err(RuleError::PassNotAllowed); }
+            if !self.drawn_card { return Err(RuleError::MustDrawFirst); }
+        }
+        if self.rules.after_draw == AfterDraw::MustPlayDrawn && self.drawn().is_some_and(|card| self.can_play(card))
+        {
+            return Err(RuleError::MustPlayDrawn);
+        }
+
+        // A stacked penalty with no cards to pay it is dropped
+        self.penalty = 0;
+        self.drawn_card = false;
+        Ok(vec![self.end_turn()])
+    }
+
+    fn match_row(&mut self, seat: usize, row: usize, first: usize, second: Option<usize>) -> Result<Vec<Event>, RuleError>
+    {
+        if !self.rules.dos { return Err(RuleError::NotDosMode); }
+        self.check_turn(seat)?;
+        let target = *self.centre_row.get(row).ok_or(RuleError::NoSuchRowCard)?;
+        let hand = &self.hands[seat];
+        if first >= hand.len() || second.is_some_and(|second| second >= hand.len() || second == first)
+        {
+            return Err(RuleError::NoSuchCard);
+        }
+        let cards: Vec<Card> = std::iter::once(first).chain(second).map(|index| hand[index]).collect();
+        if !dos_match(&target, &cards) { return Err(RuleError::NoMatch); }
+
+        // Later index goes first, so that the other one stays in place
+        let mut indices: Vec<usize> = std::iter::once(first).chain(second).collect();
+        indices.sort_unstable_by(|a, b| b.cmp(a));
+        for index in indices
+        {
+            self.hands[seat].remove(index);
+        }
+        self.centre_row.remove(row);
+        self.discard_pile.push(target);
+        self.discard_pile.extend(&cards);
+        self.matched = true;
+
+        let color_match = target.suite != Suit::Black && cards.iter().all(|card| card.suite == target.suite);
+        let mut events = vec![Event::Matched { seat, target, first: cards[0], second: cards.get(1).copied(), color_match }];
+        if self.hands[seat].is_empty()
+        {
+            events.push(Event::RoundWon { seat });
+            return Ok(events);
+        }
+        // A color match lets a seat add a card to the centre row, a double one also
+        // makes everybody else draw a card
+        if color_match
+        {
+            self.owed_to_row += 1;
+            if cards.len() == 2
+            {
+                for other in (0..self.hands.len()).filter(|&other| other != seat)
+                {
+                    events.extend(self.give_cards(other, 1));
+                }
+            }
+        }
+        Ok(events)
+    }
+
+    fn add_to_row(&mut self, seat: usize, index: usize) -> Result<Vec<Event>, RuleError>
+    {
+        if !self.rules.dos { return Err(RuleError::NotDosMode); }
+        self.check_turn(seat)?;
+        if index >= self.hands[seat].len() { return Err(RuleError::NoSuchCard); }
+        // Without a match this card ends a turn, but only once drawing didn't help
+        let ends_turn = self.owed_to_row == 0;
+        if ends_turn
+        {
+            if self.matched { return Err(RuleError::NothingToAdd); }
+            if self.can_draw() && !self.drawn_card { return Err(RuleError::MustDrawFirst); }
+        }
+
+        self.owed_to_row = self.owed_to_row.saturating_sub(1);
+        let card = self.hands[seat].remove(index);
+        self.centre_row.push(card);
+        let mut events = vec![Event::AddedToRow { seat, card }];
+        if self.hands[seat].is_empty()
+        {
+            events.push(Event::RoundWon { seat });
+            return Ok(events);
+        }
+        if ends_turn { events.extend(self.end_dos_turn()); }
+        Ok(events)
+    }
+
+    fn call(&mut self, seat: usize) -> Result<Vec<Event>, RuleError>
+    {
+        if self.uncalled != Some(seat) { return Err(RuleError::NothingToCall); }
+        self.uncalled = None;
+        Ok(vec![Event::Called { seat }])
+    }
+
+    fn catch(&mut self, seat: usize, target: usize) -> Result<Vec<Event>, RuleError>
+    {
+        if seat == target || seat >= self.hands.len() || self.uncalled != Some(target) { return Err(RuleError::NothingToCatch); }
+        self.uncalled = None;
+        let mut events = vec![Event::Caught { seat, target }];
+        events.extend(self.give_cards(target, CATCH_PENALTY));
+        Ok(events)
+    }
+
+    // Centre row is topped up before the next seat gets it
+    fn end_dos_turn(&mut self) -> Vec<Event>
+    {
+        let mut events = self.refill_row();
+        self.matched = false;
+        self.drawn_card = false;
+        events.push(self.end_turn());
+        events
+    }
+
+    fn refill_row(&mut self) -> Vec<Event>
+    {
+        let mut events = vec![];
+        while self.centre_row.len() < CENTRE_ROW_SIZE
+        {
+            let Some(card) = self.take_card(&mut events) else { break; };
+            self.centre_row.push(card);
+            events.push(Event::RowRefilled { card });
+        }
+        events
+    }
+
+    // Drawing until something fits stops at a card that can be played, or in DOS mode
+    // once any match can be made
+    fn drawn_card_fits(&self, seat: usize, card: &Card) -> bool
+    {
+        if self.rules.dos { !self.matches(seat).is_empty() } else { self.can_play(card) }
+    }
+
+    fn time_out(&mut self, seat: usize) -> Result<Vec<Event>, RuleError>
+    {
+        if !self.rules.turbo { return Err(RuleError::NotTurbo); }
+        self.check_turn(seat)?;
+
+        let mut events = vec![Event::TimedOut { seat }];
+        // A wild card keeps the color from before it, a Seven swaps nothing
+        if self.choosing_color || self.choosing_swap
+        {
+            if self.choosing_color { events.push(Event::ColorChosen { seat, suit: self.color }); }
+            self.choosing_color = false;
+            self.choosing_swap = false;
+            events.extend(self.resolve(*self.top_card()));
+            return Ok(events);
+        }
+        // Taking a penalty ends a turn by itself
+        if self.penalty > 0
+        {
+            events.extend(self.draw(seat)?);
+            return Ok(events);
+        }
+
+        if !self.drawn_card && !self.matched
+        {
+            events.extend(self.give_cards(seat, 1));
+        }
+        self.owed_to_row = 0;
+        if self.rules.dos
+        {
+            events.extend(self.end_dos_turn());
+        }
+        else
+        {
+            self.drawn_card = false;
+            events.push(self.end_turn());
+        }
+        Ok(events)
+    }
+
+    fn check_turn(&self, seat: usize) -> Result<(), RuleError>
+    {
+        if seat == self.turn { Ok(()) } else { Err(RuleError::NotYourTurn) }
+    }
+
+    // Color or swap that the current seat has to pick before anything else
+    fn check_no_choice(&self) -> Result<(), RuleError>
+    {
+        if self.choosing_color { return Err(RuleError::MustChooseColor); }
+        if self.choosing_swap { return Err(RuleError::MustPickSwap); }
+        Ok(())
+    }
+
+    fn check_nothing_pending(&self) -> Result<(), RuleError>
+    {
+        self.check_no_choice()?;
+        if self.challenge.is_some() { return Err(RuleError::MustAnswerChallenge); }
+        Ok(())
+    }
+
+    fn end_turn(&mut self) -> Event
+    {
+        self.turn = self.next_seat(self.turn);
+        self.turns += 1;
+        Event::TurnChanged { seat: self.turn }
+    }
+
+    // Next seat loses its turn. Called before end_turn, which then moves past it
+    fn skip_next(&mut self) -> Event
+    {
+        self.turn = self.next_seat(self.turn);
+        Event::TurnSkipped { seat: self.turn }
+    }
+
+    // Penalty draw outside of a seat's own turn. Gives fewer cards if there are none left to draw
+    fn give_cards(&mut self, seat: usize, count: usize) -> Vec<Event>
+    {
+        let mut events = vec![];
+        for _ in 0..count
+        {
+            if let Some(card) = self.take_card(&mut events)
+            {
+                self.hands[seat].push(card);
+                events.push(Event::CardDrawn { seat, card });
+            }
+        }
+        events
+    }
+
+    // Top card of a deck. An empty deck is refilled first with a discard pile, except its top card
+    fn take_card(&mut self, events: &mut Vec<Event>) -> Option<Card>
+    {
+        if self.deck.is_empty() && self.discard_pile.len() > 1
+        {
+            let top = self.discard_pile.pop().unwrap();
+            self.deck = std::mem::replace(&mut self.discard_pile, vec![top]);
+            self.deck.shuffle(&mut self.rng);
+            events.push(Event::DeckReshuffled { count: self.deck.len() });
+        }
+        self.deck.pop()
+    }
+}
+
+//----------------------------------------------------------------------------------
+//  Scoring
+//----------------------------------------------------------------------------------
+
+// Points of every seat over all rounds of a match
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Scoreboard
+{
+    scores: Vec<usize>,
+    rounds: usize,
+}
+
+impl Scoreboard
+{
+    pub fn new(num_seats: usize) -> Self
+    {
+        Scoreboard { scores: vec![0; num_seats], rounds: 0 }
+    }
+
+    pub fn scores(&self) -> &[usize] { &self.scores }
+
+    pub fn rounds(&self) -> usize { self.rounds }
+
+    // Give points of a finished round to its winner. Returns who got how many
+    pub fn record(&mut self, engine: &GameEngine) -> Option<(usize, usize)>
+    {
+        let winner = engine.winner()?;
+        let points = engine.round_points();
+        self.scores[winner] += points;
+        self.rounds += 1;
+        Some((winner, points))
+    }
+
+    // Seat with the most points, once somebody got to the target
+    pub fn match_winner(&self, target_score: usize) -> Option<usize>
+    {
+        let (seat, score) = self.scores.iter().enumerate().max_by_key(|&(seat, score)| (score, std::cmp::Reverse(seat)))?;
+        if *score >= target_score { Some(seat) } else { None }
+    }
+}
+
+//----------------------------------------------------------------------------------
+//  Helper functions
+//----------------------------------------------------------------------------------
+
+// Number cards are worth their face value, action cards 20 and wild cards 50
+pub fn card_points(card: &Card) -> usize
+{
+    match card.rank
+    {
+        Rank::Skip | Rank::Reverse | Rank::Draw2 => 20,
+        Rank::Wild | Rank::WildDraw4 => 50,
+        number => number as usize,
+    }
+}
+
+// Number of a number card, None for action and wild cards
+pub fn card_number(card: &Card) -> Option<usize>
+{
+    if card.rank as usize <= Rank::Nine as usize { Some(card.rank as usize) } else { None }
+}
+
+// Whether one card or two cards can take `target` off a centre row. Wild cards stand for
+// any number, on either side
+pub fn dos_match(target: &Card, cards: &[Card]) -> bool
+{
+    if cards.is_empty() || cards.len() > 2 { return false; }
+    if cards.iter().any(|card| card.rank != Rank::Wild && card_number(card).is_none()) { return false; }
+    let Some(number) = card_number(target) else { return target.rank == Rank::Wild; };
+
+    let wilds = cards.iter().filter(|card| card.rank == Rank::Wild).count();
+    let sum: usize = cards.iter().filter_map(card_number).sum();
+    if wilds > 0 { sum <= number } else { sum == number }
+}
+
+// Decks asked for by rules, and more if it takes them to leave at least as many cards
+// to draw as were dealt
+pub fn table_deck(rules: &Rules) -> Vec<Card> {
+    let single = new_deck(rules);
+    if single.is_empty() { return single; }
+
+    let mut deck = Vec::new();
+    while deck.len() < single.len() * rules.deck_multiplier.max(1) || deck.len() < rules.num_players * rules.hand_size * 2
+    {
+        // Copies get ids of their own
+        let offset = deck.len();
+        deck.extend(single.iter().map(|card| Card { id: card.id + offset, ..*card }));
+    }
+    deck
+}
+
+// One deck with cards allowed by rules, unshuffled. Ids are unique so that sprites can be matched to cards
+pub fn new_deck(rules: &Rules) -> Vec<Card> {
+    let mut deck: Vec<Card> = Vec::new();
+    let last_rank = if rules.action_cards && !rules.dos { Rank::Draw2 } else { Rank::Nine };
+    for color in COLORS
+    {
+        // Every rank up to Draw2 comes in colors
+        for rank in 0..=last_rank as usize
+        {
+            let copies = if rank == Rank::Zero as usize { rules.rank_copies.div_ceil(2) } else { rules.rank_copies };
+            for _ in 0..copies
+            {
+                deck.push(Card {
+                    rank: Rank::from_usize(rank).unwrap(),
+                    suite: color,
+                    id: deck.len(),
+                });
+            }
+        }
+    }
+    let wild_ranks: &[Rank] = match (rules.wild_cards, rules.dos)
+    {
+        (false, _) => &[],
+        (true, false) => &[Rank::Wild, Rank::WildDraw4],
+        (true, true) => &[Rank::Wild],
+    };
+    for &rank in wild_ranks
+    {
+        for _ in 0..WILD_COPIES
+        {
+            deck.push(Card { rank, suite: Suit::Black, id: deck.len() });
+        }
+    }
+    deck
+}
+
+impl Rules
+{
+    // Whether a game can be dealt with these rules
+    pub fn validate(&self) -> Result<(), RuleError>
+    {
+        match self.num_players
+        {
+            0 => Err(RuleError::NoPlayers),
+            n if n > MAX_PLAYERS => Err(RuleError::TooManyPlayers),
+            _ if self.hand_size == 0 => Err(RuleError::EmptyHands),
+            _ if self.turn_time == 0 => Err(RuleError::NoTurnTime),
+            _ if (1..MIN_PENALTY_CAP).contains(&self.penalty_cap) => Err(RuleError::PenaltyCapTooLow),
+            n => {
+                // However a deck is shuffled, a colored card is left to start the discard pile
+                // after dealing, and the rest fills a DOS centre row
+                let deck = table_deck(self);
+                let colored = deck.iter().filter(|card| card.suite != Suit::Black).count();
+                let row = if self.dos { CENTRE_ROW_SIZE } else { 0 };
+                if n * self.hand_size >= colored || n * self.hand_size + 1 + row > deck.len()
+                {
+                    return Err(RuleError::NotEnoughCards);
+                }
+                Ok(())
+            },
+        }
+    }
+}
+
+impl Default for Rules {
+    fn default() -> Self {
+        Rules {
+            num_players: 5,
+            stackable_cards: false,
+            stack_draw4: false,
+            progressive: false,
+            penalty_cap: 0,
+            deflect_penalty: false,
+            turbo: false,
+            turn_time: TURN_TIME,
+            clockwise: false,
+            no_skip: false,
+            draw_until_playable: false,
+            after_draw: AfterDraw::AnyCard,
+            wild_draw4_challenge: false,
+            seven_o: false,
+            jump_in: false,
+            target_score: TARGET_SCORE,
+            hand_size: HAND_SIZE,
+            rank_copies: RANK_COPIES,
+            action_cards: true,
+            wild_cards: true,
+            deck_multiplier: 1,
+            dos: false,
+            last_call: true,
+            catch_delay: CATCH_DELAY,
+            bot_levels: [BotLevel::Normal; MAX_PLAYERS],
+        }
+    }
+}
+
+impl Action
+{
+    // Seat that takes an action
+    pub fn seat(&self) -> usize
+    {
+        match *self
+        {
+            Action::Draw { seat }
+            | Action::Play { seat, .. }
+            | Action::Pass { seat }
+            | Action::ChooseColor { seat, .. }
+            | Action::Challenge { seat }
+            | Action::SwapHands { seat, .. }
+            | Action::Match { seat, .. }
+            | Action::AddToRow { seat, .. }
+            | Action::Call { seat }
+            | Action::Catch { seat, .. }
+            | Action::TimeOut { seat } => seat,
+        }
+    }
+}
+
+// Allows to format an enum into string
+impl fmt::Display for Rank {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Debug::fmt(self, f)
+    }
+}
+
+impl fmt::Display for Suit {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Debug::fmt(self, f)
+    }
+}
+
+impl fmt::Display for RuleError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let message = match self {
+            RuleError::NotEnoughCards => "Not enough cards in a deck to deal for everyone",
+            RuleError::NotYourTurn => "It is not your turn",
+            RuleError::NoSuchCard => "There is no such card in a hand",
+            RuleError::CardDoesNotMatch => "Card doesn't match the top of a discard pile",
+            RuleError::AlreadyDrawn => "A card was already drawn this turn",
+            RuleError::MustDrawFirst => "Draw a card before skipping a turn",
+            RuleError::PassNotAllowed => "Skipping a turn is disabled",
+            RuleError::DeckEmpty => "No cards left inside a deck",
+            RuleError::MustChooseColor => "Choose a color for the wild card first",
+            RuleError::NoColorToChoose => "There is no wild card to choose a color for",
+            RuleError::NotAColor => "Black can't be chosen as a color",
+            RuleError::MustAnswerChallenge => "Challenge the Wild Draw 4 or draw cards",
+            RuleError::NothingToChallenge => "There is no Wild Draw 4 to challenge",
+            RuleError::MustStackOrDraw => "Stack a draw card or take the penalty",
+            RuleError::RoundOver => "Round is over, somebody has no cards left",
+            RuleError::NoPlayers => "At least one player is needed",
+            RuleError::TooManyPlayers => "There are not enough seats at a table for that many players",
+            RuleError::EmptyHands => "Hands have to start with at least one card",
+            RuleError::NoTurnTime => "Turns have to last at least a second",
+            RuleError::PenaltyCapTooLow => "A stacked penalty can't be capped below 4 cards",
+            RuleError::MustPickSwap => "Choose whom to swap hands with first",
+            RuleError::NoSwapToPick => "There is no Seven to swap hands for",
+            RuleError::NoSuchSeat => "Hands can only be swapped with another seat at a table",
+            RuleError::MustPlayDrawn => "The drawn card fits and has to be played",
+            RuleError::OnlyDrawnCard => "Only the drawn card can be played",
+            RuleError::NotDosMode => "There is a centre row only in DOS mode",
+            RuleError::MatchInDosMode => "Cards are matched against the centre row in DOS mode",
+            RuleError::NoSuchRowCard => "There is no such card in the centre row",
+            RuleError::NoMatch => "Cards don't add up to the centre row card",
+            RuleError::AlreadyMatched => "Cards can't be drawn after a match",
+            RuleError::MustAddToRow => "Put a card on the centre row first",
+            RuleError::NothingToAdd => "No card is owed to the centre row",
+            RuleError::NothingToCall => "Only the last cards of a hand are called",
+            RuleError::NothingToCatch => "Nobody has missed a call",
+            RuleError::NotTurbo => "Turns run out only in turbo mode",
+        };
+        f.write_str(message)
+    }
+}
+
+impl std::error::Error for RuleError {}
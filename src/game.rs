@@ -9,21 +9,25 @@
 *                                               *
 ************************************************/
 
-use std::fmt::{self, Debug};   // Conver enum variants into string
-use std::mem;               // Conver variants of enum into integer
+use std::collections::HashMap;
+use std::time::Duration;
 use bevy::prelude::*;
+use bevy::tasks::{AsyncComputeTaskPool, Task};
+use futures::FutureExt;
 use crate::{despawn_screen, GameState, Rules};
-use rand::{seq::SliceRandom, thread_rng};
+use crate::engine::{self, Action, Card, GameEngine, RuleError, Scoreboard, Suit, MAX_PLAYERS};
+use crate::replays::{Replay, ReplayError, Replays};
+use crate::bots::{move_rng, BotLevel, BotStrategy, BotView, MoveChoice};
+use crate::search::{SearchBot, SEARCH_ROUNDS};
+use rand::{rngs::StdRng, thread_rng, Rng, SeedableRng};
 use num_derive::FromPrimitive;  //derive a trait on enum to access it with integer
 use num::FromPrimitive;         //access enum values via integer
-use crate::menu::TEXT_COLOR;
+use crate::menu::{MenuMessage, TEXT_COLOR};
 
 //----------------------------------------------------------------------------------
 //  Game configurations
 //----------------------------------------------------------------------------------
 
-const HAND_SIZE: usize = 7;
-
 const PLAYERS_WIDTH_DISTANCE: f32 = 500.0;
 const PLAYERS_HEIGHT_DISTANCE: f32 = 280.0;
 const PLAYRES_Y_OFFSET: f32 = 50.0;
@@ -37,48 +41,43 @@ const ENEMY_CARDS_SPACING: f32 = 12.0;
 
 const DECK_DISCARD_DISTANCE: f32 = 100.0;
 const FALLBACK_DECK_COLLIDER: Vec2 = Vec2::new(100.0, 150.0);
+// Frame around a wild card on the discard pile, painted with a chosen color
+const COLOR_INDICATOR_SIZE: Vec2 = Vec2::new(180.0, 244.0);
+const PENALTY_TEXT_OFFSET_X: f32 = 150.0;
+const PENALTY_TEXT_FONT_SIZE: f32 = 60.0;
+// DOS mode centre row above a deck and a discard pile
+const ROW_CARD_SCALE: Vec3 = Vec3::new(0.5, 0.5, 0.0);
+const CENTRE_ROW_Y: f32 = 150.0;
+const CENTRE_ROW_SPACING: f32 = 110.0;
+// Cards picked for a DOS match stick out of a hand
+const SELECTED_CARD_RAISE: f32 = 30.0;
+// Card the main player just drew is tinted until the turn goes on
+const DRAWN_CARD_TINT: Color = Color::rgb(1.0, 0.85, 0.45);
+
+// Seconds bots need to notice they can jump in, shorter than a turn of a bot
+const BOT_JUMP_IN_DELAY: f32 = 0.7;
+// Seconds between moves of bots, much shorter in turbo mode
+const BOT_DELAY: f32 = 1.0;
+const TURBO_BOT_DELAY: f32 = 0.3;
+// How often a bot forgets to call its last card
+const BOT_FORGET_CALL_CHANCE: f64 = 0.2;
+
+// Card backs flying from a discard pile to a deck when it is reshuffled
+const RESHUFFLE_ANIMATION_CARDS: usize = 8;
+const RESHUFFLE_CARD_DELAY: f32 = 0.06;
+const RESHUFFLE_FLIGHT_TIME: f32 = 0.35;
+// Drawn cards fly from a deck into a hand one after another
+const DRAW_CARD_DELAY: f32 = 0.25;
+const DRAW_FLIGHT_TIME: f32 = 0.3;
 
 const NAME_TEXT_OFFSET_X: f32 = -50.0;
 const NAME_TEXT_OFFSET_Y: f32 = 100.0;
 const NAME_TEXT_FONT_SIZE: f32 = 40.0;
 
-
-#[derive(Clone, Copy, PartialEq, Eq, Debug, FromPrimitive)]
-enum Rank
-{
-    Zero,
-    One,
-    Two,
-    Three,
-    Four,
-    Five,
-    Six,
-    Seven,
-    Eight,
-    Nine,
-    Skip,
-    Reverse,
-    Draw2,
-    // Wild,
-    // WildDraw4
-}
-
-#[derive(Clone, Copy, PartialEq, Eq, Debug, FromPrimitive)]
-enum Suit
-{
-    Red,
-    Blue,
-    Yellow,
-    Green,
-}
-
-#[derive(Debug)]
-struct Card
-{
-    rank: Rank,
-    suite: Suit,
-    id: usize,
-}
+// Seat of a MainPlayer inside GameEngine
+pub const MAIN_SEAT: usize = PlayerName::MainPlayer as usize;
+// Every seat the engine allows needs a name
+const _: () = assert!(PlayerName::Void as usize >= MAX_PLAYERS);
 
 //----------------------------------------------------------------------------------
 //  Components and Bundles
@@ -106,22 +105,36 @@ pub enum PlayerName
 struct Id(usize);
 
 #[derive(Component, Debug)]
-struct Deck
-{
-    cards: Vec<Card>,
-}
+struct Deck;
 
 #[derive(Component, Debug)]
-struct DiscardPile
+struct DiscardPile;
+
+#[derive(Component)]
+struct ColorIndicator;
+
+// Cards that a seat unable to stack will have to draw
+#[derive(Component)]
+struct PenaltyText;
+
+// Sprite that only moves across a table and disappears, cards themselves are already in place
+#[derive(Component)]
+struct FlyingCard
 {
-    cards: Vec<Card>,
+    from: Vec3,
+    to: Vec3,
+    // Seconds to wait before taking off, the rest of a timer is spent in flight
+    delay: f32,
+    timer: Timer,
+    // Id of a card in a hand that stays hidden until this one lands
+    card: Option<usize>,
 }
 
+// Cards of a player are kept inside GameEngine, under a seat equal to his PlayerName
 #[derive(Component, Debug)]
 struct Player
 {
     pos: Vec3,
-    cards: Vec<Card>,
 }
 
 #[derive(Component)]
@@ -143,22 +156,100 @@ pub struct GameItem;
 //  Resources and Events
 //----------------------------------------------------------------------------------
 
-#[derive(Resource, Debug)]
-pub struct GameplayState
-{
-    pub player_turn: PlayerName,
-    pub player_drawn_card: bool,
-}
+// The whole table is owned by the engine. Systems below only turn input into actions
+// and lay out sprites after the engine has changed
+impl Resource for GameEngine {}
 
 struct PlayCard(usize);
 
+// Index of a centre row card clicked on in DOS mode
+struct MatchRow(usize);
+
+// Sent by the in-game UI to put a picked card on the centre row in DOS mode
+#[derive(Default)]
+pub struct AddToRow;
+
+#[derive(Default)]
+pub struct DrawCard;
+
+// Sent by the in-game UI to end a turn after drawing a card
+#[derive(Default)]
+pub struct SkipTurn;
+
+// Sent by the in-game UI after the main player has played a wild card
+pub struct ChooseColor(pub Suit);
+
 #[derive(Default)]
-struct DrawCard;
+pub struct ChallengeDraw4;
+
+// Sent by the in-game UI with a seat to swap hands with after the main player has played a Seven
+pub struct SwapWith(pub usize);
 
+// Sent by the in-game UI when the main player calls his last card
+#[derive(Default)]
+pub struct CallLastCard;
+
+// Sent by the in-game UI to catch a seat that missed its call
+#[derive(Default)]
+pub struct CatchCall;
+
+// Everything the engine reports after an action was applied, in order, for animations and HUD
+pub struct TableEvent(pub engine::Event);
+
+// A bot thinks about its move in `task` while the delay runs, and moves once both are done
 #[derive(Resource)]
 struct BotWaiting
 {
     event_timer: Timer,
+    task: Option<Task<Action>>,
+    delay_over: bool,
+}
+
+// Seed a match is played with. Dealing, reshuffles and bots all follow from it, so a match
+// played again with the same seed and the same moves of the main player goes the same way
+#[derive(Resource, Default)]
+pub struct GameSeed
+{
+    // Seed entered in the menu, every match gets a random one without it
+    pub fixed: Option<u64>,
+    // Seed of the match being played
+    pub current: u64,
+    // Seed the deck of the current round was shuffled with, drawn from the match one
+    pub round: u64,
+}
+
+// Randomness of a match, seeded anew from GameSeed when it starts
+#[derive(Resource)]
+struct GameRng(StdRng);
+
+// Turbo mode countdown of a seat whose turn it is, started anew for every turn
+#[derive(Resource)]
+pub struct TurnClock
+{
+    seat: usize,
+    // Turn of the engine the countdown runs for, a seat may get several in a row
+    turn: usize,
+    timer: Timer,
+}
+
+// Bots catch a missed call once `timer` is up. Other bots don't move until then
+#[derive(Resource)]
+struct BotCatch
+{
+    target: Option<usize>,
+    timer: Timer,
+}
+
+// Ids of cards of the main player picked for a DOS match, in order of clicks
+#[derive(Resource, Default)]
+struct SelectedCards(Vec<usize>);
+
+// Bots get one chance to jump in on every card put on the pile, after a reaction delay
+#[derive(Resource)]
+struct BotJumpIn
+{
+    top_card: Option<usize>,
+    timer: Timer,
 }
 
 //----------------------------------------------------------------------------------
@@ -173,14 +264,69 @@ impl Plugin for GamePlugin
     {
         app.add_event::<DrawCard>()
             .add_event::<PlayCard>()
+            .add_event::<SkipTurn>()
+            .add_event::<ChooseColor>()
+            .add_event::<ChallengeDraw4>()
+            .add_event::<SwapWith>()
+            .add_event::<MatchRow>()
+            .add_event::<AddToRow>()
+            .add_event::<CallLastCard>()
+            .add_event::<CatchCall>()
+            .add_event::<TableEvent>()
             .init_resource::<BotWaiting>()
+            .init_resource::<BotJumpIn>()
+            .init_resource::<SelectedCards>()
+            .init_resource::<BotCatch>()
+            .init_resource::<TurnClock>()
+            .init_resource::<GameSeed>()
+            .init_resource::<GameRng>()
+            // A round that couldn't be dealt goes back to the menu without a table
+            .configure_set(OnUpdate(GameState::Game).run_if(resource_exists::<GameEngine>()))
+            // State is already the next one on exit, a replay doesn't start a match and a round
+            // after the last one of a match does
+            .add_system(new_match.run_if(in_state(GameState::Game)).in_schedule(OnExit(GameState::Menu)))
+            .add_system(new_match.run_if(in_state(GameState::Game).and_then(match_over)).in_schedule(OnExit(GameState::RoundOver)))
             .add_system(setup.in_schedule(OnEnter(GameState::Game)))
-            .add_systems((bot_play, test).in_set(OnUpdate(GameState::Game)))
+            .add_systems((bot_play, bot_jump_in, test).in_set(OnUpdate(GameState::Game)))
+            .add_system(record_replay.in_schedule(OnExit(GameState::Game)))
+            .add_system(bot_catch.before(bot_play).in_set(OnUpdate(GameState::Game)))
+            .add_system(turn_countdown.in_set(OnUpdate(GameState::Game)))
             .add_system(check_deck_bounds.run_if(mouse_pressed).in_set(OnUpdate(GameState::Game)))
             // EventWriter goes before EventReader
             .add_system(draw_card.after(check_deck_bounds).in_set(OnUpdate(GameState::Game)))
             .add_system(play_card.after(check_deck_bounds).in_set(OnUpdate(GameState::Game)))
-            .add_system(despawn_screen::<GameItem>.in_schedule(OnExit(GameState::Game)));
+            .add_systems((skip_turn, choose_color, challenge_draw4, swap_hands).in_set(OnUpdate(GameState::Game)))
+            .add_systems((match_row, add_to_row).after(check_deck_bounds).in_set(OnUpdate(GameState::Game)))
+            .add_systems((call_last_card, catch_call).in_set(OnUpdate(GameState::Game)))
+            // Sprites follow the engine once every action of a frame was applied
+            .add_system(sync_cards
+                .after(draw_card)
+                .after(play_card)
+                .after(skip_turn)
+                .after(choose_color)
+                .after(challenge_draw4)
+                .after(swap_hands)
+                .after(match_row)
+                .after(add_to_row)
+                .after(call_last_card)
+                .after(catch_call)
+                .after(bot_catch)
+                .after(turn_countdown)
+                .after(bot_play)
+                .after(bot_jump_in)
+                .run_if(resource_exists::<GameEngine>().and_then(resource_changed::<GameEngine>().or_else(resource_changed::<SelectedCards>())))
+                .run_if(on_table))
+            .add_system(check_round_over
+                .after(sync_cards)
+                .run_if(resource_exists::<GameEngine>().and_then(resource_changed::<GameEngine>()))
+                .in_set(OnUpdate(GameState::Game)))
+            // A replay is played back through the same sprites and animations
+            .add_systems((animate_draws.after(sync_cards), animate_reshuffle).distributive_run_if(on_table))
+            // Cards drawn with the last move of a round still land under its results
+            .add_systems((move_flying_cards, show_landed_cards).chain())
+            // A table stays under the results of a round, so it is cleared only when leaving them
+            .add_system(despawn_screen::<GameItem>.before(setup).in_schedule(OnEnter(GameState::Game)))
+            .add_system(despawn_screen::<GameItem>.in_schedule(OnEnter(GameState::Menu)));
     }
 }
 
@@ -188,46 +334,66 @@ impl Plugin for GamePlugin
 //  Systems
 //----------------------------------------------------------------------------------
 
+// Leaving the menu or a finished match always starts counting points from zero, with a new seed
+// unless one is fixed
+fn new_match(
+    rules: Res<Rules>,
+    mut scoreboard: ResMut<Scoreboard>,
+    mut seed: ResMut<GameSeed>,
+    mut rng: ResMut<GameRng>,
+) {
+    *scoreboard = Scoreboard::new(rules.num_players);
+    seed.current = seed.fixed.unwrap_or_else(|| thread_rng().gen());
+    rng.0 = StdRng::seed_from_u64(seed.current);
+    info!("Match seed: {}", seed.current);
+}
+
 fn setup(
     mut commands: Commands,
     asset_server: Res<AssetServer>,
     rules: Res<Rules>,
+    mut bot_waiting: ResMut<BotWaiting>,
+    mut seed: ResMut<GameSeed>,
+    mut rng: ResMut<GameRng>,
+    mut next_state: ResMut<NextState<GameState>>,
 ) {
+    // Create a deck, shuffle cards in it and deal them. A round keeps its own seed, to be
+    // replayed from it
+    seed.round = rng.0.gen();
+    let engine = match GameEngine::new(*rules, &mut StdRng::seed_from_u64(seed.round))
+    {
+        Ok(engine) => engine,
+        // No table is left behind, the menu tells why
+        Err(err) => {
+            warn!("Round can't be dealt: {}", err);
+            commands.remove_resource::<GameEngine>();
+            commands.insert_resource(MenuMessage(err.to_string()));
+            next_state.set(GameState::Menu);
+            return;
+        },
+    };
+    spawn_table(&mut commands, &asset_server, &engine);
+
+    // Bots hurry up in turbo mode
+    let bot_delay = if rules.turbo { TURBO_BOT_DELAY } else { BOT_DELAY };
+    bot_waiting.event_timer = Timer::from_seconds(bot_delay, TimerMode::Repeating);
+
+    commands.insert_resource(TurnClock::new(&engine));
+    commands.insert_resource(engine);
+    commands.insert_resource(SelectedCards::default());
+}
+
+// Players, a deck and a discard pile laid out for the rules of an engine. Sprites of the
+// cards are spawned by sync_cards as soon as the engine is in place
+pub fn spawn_table(commands: &mut Commands, asset_server: &AssetServer, engine: &GameEngine) {
     /********* Initialization *********/
 
+    let rules = engine.rules();
     let center = Vec3::ZERO;
     let angle: f32 = 360.0 / rules.num_players as f32;
     let font = asset_server.load("fonts/FiraSans-Bold.ttf");
     let tex_back = asset_server.load("Back.png");
 
-    // Automatically calculate the amount of possible combinations of card X color
-    let card_variants = mem::variant_count::<Rank>();
-    let card_colors = mem::variant_count::<Suit>();
-    // Every card has a unique ID to help access SpriteBundle
-    let mut ids: Vec<usize> = (0..card_colors * card_variants * 4).collect();
-    ids.shuffle(&mut thread_rng());
-    // Create a deck and shuffle cards in it
-    let mut new_deck: Vec<Card> = Vec::new();
-    for color in 0..card_colors
-    {
-        for rank in 0..card_variants
-        {
-            for _ in 0..2
-            {
-                new_deck.push(Card {
-                    rank: Rank::from_usize(rank).unwrap(),
-                    suite: Suit::from_usize(color).unwrap(),
-                    id: ids.pop().unwrap(),
-                });
-            }
-        }
-    }
-    new_deck.shuffle(&mut thread_rng());
-    commands.insert_resource(GameplayState {
-        player_turn: PlayerName::MainPlayer,
-        player_drawn_card: false,
-    });
-
     /********* Create players *********/
 
     for i in 0..rules.num_players
@@ -237,35 +403,18 @@ fn setup(
         let x = center.x + theta.cos() * PLAYERS_WIDTH_DISTANCE;
         let y = center.y + PLAYRES_Y_OFFSET + theta.sin() * PLAYERS_HEIGHT_DISTANCE;
 
-        // move cards from a deck to a player's hand
-        let mut player_hand: Vec<Card> = new_deck.drain(..HAND_SIZE).collect();
-
         // If not a MainPlayer
-        if i != 0
+        if i != MAIN_SEAT
         {
-            for (j, card) in player_hand.iter_mut().enumerate()
-            {
-                commands.spawn((
-                    CardBundle {
-                        sprite: SpriteBundle {
-                            texture: tex_back.clone(),
-                            transform: Transform::from_xyz(x + (j as f32) * ENEMY_CARDS_SPACING, y, j as f32).with_scale(ENEMY_CARD_SCALE),
-                            ..default()
-                        },
-                        id: Id(card.id),
-                    },
-                    GameItem
-                ));
-            }
             // Spawn a player and give him a name from enum of PlayerName
             commands.spawn((PlayerName::from_usize(i).unwrap(),
-                Player { pos: Vec3::new(x, y, 0.0), cards: player_hand },
+                Player { pos: Vec3::new(x, y, 0.0) },
                 GameItem,
             ));
             // Text of a Player's name on top of a hand
             commands.spawn((
                 Text2dBundle {
-                    text: Text::from_section(format!("Player {}", i + 1), TextStyle { font: font.clone(), font_size: NAME_TEXT_FONT_SIZE, color: TEXT_COLOR }),
+                    text: Text::from_section(seat_name(i), TextStyle { font: font.clone(), font_size: NAME_TEXT_FONT_SIZE, color: TEXT_COLOR }),
                     transform: Transform::from_xyz(x - NAME_TEXT_OFFSET_X, y - NAME_TEXT_OFFSET_Y, 0.0),
                     ..default()
                 },
@@ -274,35 +423,17 @@ fn setup(
         }
         else
         {
-            for (j, card) in player_hand.iter_mut().enumerate()
-            {
-                // If MainPlayer's hand - load a front image instead of a back image
-                let image_name = format!("{}_{}.png", card.suite.to_string(), card.rank.to_string());
-
-                commands.spawn((
-                    CardBundle {
-                        sprite: SpriteBundle {
-                            texture: asset_server.load(image_name),
-                            transform: Transform::from_xyz(x + (j as f32) * PLAYER_CARDS_SPACING, y, j as f32).with_scale(PLAYER_CARD_SCALE),
-                            ..default()
-                        },
-                        id: Id(card.id),
-                    },
-                    GameItem
-                ));
-
-            }
             // Spawn a player and give him a MainPlayer component to access him directly without
             // quering every player in a game and filtering a MainPlayer
             commands.spawn((PlayerName::MainPlayer,
-                Player { pos: Vec3::new(x, y, 0.0), cards: player_hand },
+                Player { pos: Vec3::new(x, y, 0.0) },
                 MainPlayer,
                 GameItem,
             ));
             // Text of a Player's name on top of a hand
             commands.spawn((
                 Text2dBundle {
-                    text: Text::from_section("Main Player", TextStyle { font: font.clone(), font_size: NAME_TEXT_FONT_SIZE, color: TEXT_COLOR }),
+                    text: Text::from_section(seat_name(i), TextStyle { font: font.clone(), font_size: NAME_TEXT_FONT_SIZE, color: TEXT_COLOR }),
                     transform: Transform::from_xyz(x - NAME_TEXT_OFFSET_X, y - NAME_TEXT_OFFSET_Y - 20.0, 0.0),
                     ..default()
                 },
@@ -313,16 +444,33 @@ fn setup(
 
     /************ Create discard pile *************/
 
-    // TODO threow back to menu if not enough cards in deck to deal for everyone
-    // Put a card from a deck to a discard pile
-    let pile_top_card = new_deck.pop().unwrap();
-    let image_name = format!("{}_{}.png", pile_top_card.suite.to_string(), pile_top_card.rank.to_string());
-    let discard_pile = vec![pile_top_card];
     commands.spawn((
-        DiscardPile { cards: discard_pile },
+        DiscardPile,
+        SpriteBundle {
+            texture: asset_server.load(card_image_name(engine.top_card())),
+            transform: Transform::from_xyz(DECK_DISCARD_DISTANCE, 0.0, 1.0).with_scale(DISCARD_CARD_SCALE),
+            ..default()
+        },
+        GameItem,
+    ));
+    // Shown behind a discard pile only while a wild card is on top
+    commands.spawn((
+        ColorIndicator,
         SpriteBundle {
-            texture: asset_server.load(image_name),
-            transform: Transform::from_xyz(DECK_DISCARD_DISTANCE, 0.0, 0.0).with_scale(DISCARD_CARD_SCALE),
+            sprite: Sprite { custom_size: Some(COLOR_INDICATOR_SIZE), ..default() },
+            transform: Transform::from_xyz(DECK_DISCARD_DISTANCE, 0.0, 0.0),
+            visibility: Visibility::Hidden,
+            ..default()
+        },
+        GameItem,
+    ));
+
+    // Empty until a draw card gets stacked on
+    commands.spawn((
+        PenaltyText,
+        Text2dBundle {
+            text: Text::from_section("", TextStyle { font, font_size: PENALTY_TEXT_FONT_SIZE, color: TEXT_COLOR }),
+            transform: Transform::from_xyz(DECK_DISCARD_DISTANCE + PENALTY_TEXT_OFFSET_X, 0.0, 2.0),
             ..default()
         },
         GameItem,
@@ -330,9 +478,8 @@ fn setup(
 
     /************ Create a deck *************/
 
-    // Spawn a deck and put unused cards there
     commands.spawn((
-        Deck { cards: new_deck },
+        Deck,
         SpriteBundle {
             texture: tex_back,
             transform: Transform::from_xyz(-DECK_DISCARD_DISTANCE, 0.0, 0.0).with_scale(DECK_CARD_SCALE),
@@ -354,10 +501,12 @@ fn check_deck_bounds(
     all_images: Res<Assets<Image>>,
     mut deck_event: EventWriter<DrawCard>,
     mut card_event: EventWriter<PlayCard>,
-    gameplay_rules: Res<GameplayState>,
+    mut row_event: EventWriter<MatchRow>,
+    engine: Res<GameEngine>,
 ) {
-    // TODO this check shouln't be here, but mouse_pressed cant read from GameplayState
-    if gameplay_rules.player_turn != PlayerName::MainPlayer { return; }
+    // TODO this check shouln't be here, but mouse_pressed cant read from GameEngine
+    // Out of turn the engine only takes a card to jump in with
+    if engine.turn() != MAIN_SEAT && !engine.rules().jump_in { return; }
 
     let window = window_q.single();
     let (camera, camera_pos) = camera_q.single();
@@ -391,17 +540,37 @@ fn check_deck_bounds(
                 return;
         }
 
+        /********* Check if clicked on a centre row *********/
+
+        let row_x_offset = deck_image_size.x * ROW_CARD_SCALE.x / 2.0;
+        let row_y_offset = deck_image_size.y * ROW_CARD_SCALE.y / 2.0;
+        let row_len = engine.centre_row().len();
+        for j in 0..row_len
+        {
+            let x = row_card_x(j, row_len);
+            if cursor_pos.x > x - row_x_offset &&
+                cursor_pos.x < x + row_x_offset &&
+                cursor_pos.y > CENTRE_ROW_Y - row_y_offset &&
+                cursor_pos.y < CENTRE_ROW_Y + row_y_offset
+            {
+                row_event.send(MatchRow(j));
+                return;
+            }
+        }
+
         /*********Check if clicked on your hand*********/
 
         let player = player_q.single();
+        let hand = engine.hand(MAIN_SEAT);
         // Detrmin how many cards are in a hand to get rightmost bound
-        let counter: usize = player.cards.len();
+        let counter: usize = hand.len();
+        if counter == 0 { return; }
 
         // Get the size of a card to determine a collider
         let mut card_image_size: Vec2 = Vec2::ZERO;
         for (card_image, id) in card_q.iter()
         {
-            if id.0 == player.cards[0].id
+            if id.0 == hand[0].id
             {
                 // TODO prevent loading game dureing setup if image couldn't be loaded
                 card_image_size = if let Some(image) = all_images.get(&card_image) { image.size() } else { FALLBACK_DECK_COLLIDER };
@@ -426,154 +595,552 @@ fn check_deck_bounds(
 }
 
 fn draw_card(
-    mut commands: Commands,
-    mut player_q: Query<&mut Player, With<MainPlayer>>,
-    mut deck_q: Query<&mut Deck>,
-    asset_server: Res<AssetServer>,
     mut event: EventReader<DrawCard>,
-    mut game_rules: ResMut<GameplayState>,
-    rules: Res<Rules>,
+    mut engine: ResMut<GameEngine>,
+    mut table_events: EventWriter<TableEvent>,
 ) {
     // Continue only on receiving an event
     for _ in event.iter()
     {
-        if !rules.no_skip && game_rules.player_drawn_card { return; }
-
-        let mut deck = deck_q.single_mut();
-
-        // Check whether there are anymore cards in deck
-        if let Some(card) = deck.cards.pop()
+        if let Err(err) = apply_action(&mut engine, Action::Draw { seat: MAIN_SEAT }, &mut table_events)
         {
-            let mut player = player_q.single_mut();
-            // X = position of last card + spacing
-            let num_cards = player.cards.len();
-            // Load and spawn an image with a new new card
-            let image_name = format!("{}_{}.png", card.suite.to_string(), card.rank.to_string());
+            info!("{}", err);
+        }
+    }
+}
 
-            commands.spawn((
-                CardBundle {
-                    sprite: SpriteBundle {
-                        texture: asset_server.load(image_name),
-                        transform: Transform::from_xyz(player.pos.x + PLAYER_CARDS_SPACING * num_cards as f32, player.pos.y, num_cards as f32).with_scale(PLAYER_CARD_SCALE),
-                        ..default()
-                    },
-                    id: Id(card.id),
+fn play_card(
+    mut play_event: EventReader<PlayCard>,
+    mut engine: ResMut<GameEngine>,
+    mut selected: ResMut<SelectedCards>,
+    mut table_events: EventWriter<TableEvent>,
+) {
+    for event in play_event.iter()
+    {
+        // In DOS mode cards are only picked, a centre row card is clicked next to match them
+        if engine.rules().dos
+        {
+            let Some(&Card { id, .. }) = engine.hand(MAIN_SEAT).get(event.0) else { continue; };
+            match selected.0.iter().position(|&picked| picked == id)
+            {
+                Some(index) => { selected.0.remove(index); },
+                None => {
+                    // Only two cards add up to a centre row card, the oldest pick is dropped
+                    if selected.0.len() == 2 { selected.0.remove(0); }
+                    selected.0.push(id);
                 },
-                GameItem,
-            ));
-
-            player.cards.push(card);
-            game_rules.player_drawn_card = true;
+            }
+            continue;
         }
-        else
+        if let Err(err) = apply_action(&mut engine, Action::Play { seat: MAIN_SEAT, index: event.0 }, &mut table_events)
         {
-            warn!("No cards lft inside a deck.");
+            info!("{}", err);
         }
     }
 }
 
-fn play_card(
-    mut commands: Commands,
-    mut play_event: EventReader<PlayCard>,
-    mut discard_q: Query<(&mut Handle<Image>, &mut DiscardPile), Without<Deck>>,
-    mut player_q: Query<&mut Player, With<MainPlayer>>,
-    mut cards_image_q: Query<(Entity, &Handle<Image>, &mut Transform, &Id), (Without<DiscardPile>, Without<Deck>)>,
-    mut gameplay: ResMut<GameplayState>,
-    rules: Res<Rules>,
+fn skip_turn(
+    mut skip_event: EventReader<SkipTurn>,
+    mut engine: ResMut<GameEngine>,
+    mut table_events: EventWriter<TableEvent>,
 ) {
-    for event in play_event.iter()
+    for _ in skip_event.iter()
     {
-        let (mut discard_image, mut pile) = discard_q.single_mut();
-        let mut player = player_q.single_mut();
+        if let Err(err) = apply_action(&mut engine, Action::Pass { seat: MAIN_SEAT }, &mut table_events)
+        {
+            info!("{}", err);
+        }
+    }
+}
 
-        if pile.cards.last().unwrap().rank != player.cards[event.0].rank &&
-            pile.cards.last().unwrap().suite != player.cards[event.0].suite { return; }
+fn choose_color(
+    mut color_event: EventReader<ChooseColor>,
+    mut engine: ResMut<GameEngine>,
+    mut table_events: EventWriter<TableEvent>,
+) {
+    for event in color_event.iter()
+    {
+        if let Err(err) = apply_action(&mut engine, Action::ChooseColor { seat: MAIN_SEAT, suit: event.0 }, &mut table_events)
+        {
+            info!("{}", err);
+        }
+    }
+}
 
-        let card = player.cards.remove(event.0);
-        let cards_len = player.cards.len();
+fn challenge_draw4(
+    mut challenge_event: EventReader<ChallengeDraw4>,
+    mut engine: ResMut<GameEngine>,
+    mut table_events: EventWriter<TableEvent>,
+) {
+    for _ in challenge_event.iter()
+    {
+        if let Err(err) = apply_action(&mut engine, Action::Challenge { seat: MAIN_SEAT }, &mut table_events)
+        {
+            info!("{}", err);
+        }
+    }
+}
 
-        // Store Ids of cards that are to the right of a card that was played, so that we can find
-        // sprites of those cards by Id and change their positions accordingly
-        let mut ids = vec![];
-        for i in event.0..cards_len
+fn swap_hands(
+    mut swap_event: EventReader<SwapWith>,
+    mut engine: ResMut<GameEngine>,
+    mut table_events: EventWriter<TableEvent>,
+) {
+    for event in swap_event.iter()
+    {
+        if let Err(err) = apply_action(&mut engine, Action::SwapHands { seat: MAIN_SEAT, with: event.0 }, &mut table_events)
         {
-            ids.push(player.cards[i].id);
+            info!("{}", err);
         }
-        
-        for (entity, image, mut pos, id) in cards_image_q.iter_mut()
+    }
+}
+
+fn match_row(
+    mut row_event: EventReader<MatchRow>,
+    mut engine: ResMut<GameEngine>,
+    mut selected: ResMut<SelectedCards>,
+    mut table_events: EventWriter<TableEvent>,
+) {
+    for event in row_event.iter()
+    {
+        let picked = picked_indices(&engine, &selected);
+        let Some(&first) = picked.first() else {
+            info!("Pick one or two cards that add up to a centre row card");
+            continue;
+        };
+        let action = Action::Match { seat: MAIN_SEAT, row: event.0, first, second: picked.get(1).copied() };
+        match apply_action(&mut engine, action, &mut table_events)
         {
-            // Match Ids of all sprites in game. If it's the one of a card that was player -> despawn it
-            // If it's a card that is to the right of a removed card -> move it to left and down by 1
-            match id.0
-            {
-                id if id == card.id => {
-                    *discard_image = image.clone();
-                    commands.entity(entity).despawn();
-                },
-                _ if ids.contains(&id.0) => {
-                    pos.translation.x -= PLAYER_CARDS_SPACING;
-                    pos.translation.z -= 1.0;
-                },
-                _ => (),
-            }
+            Ok(()) => selected.0.clear(),
+            Err(err) => info!("{}", err),
         }
+    }
+}
 
-        // Add a played card to discard pile
-        pile.cards.push(card);
-        gameplay.player_drawn_card = false;
-        
-        // In case only 1 player in a lobby, don't pass a turn
-        // TODO stackable cards logic
-        if rules.num_players == 1 || rules.stackable_cards { return; }
+fn add_to_row(
+    mut add_event: EventReader<AddToRow>,
+    mut engine: ResMut<GameEngine>,
+    mut selected: ResMut<SelectedCards>,
+    mut table_events: EventWriter<TableEvent>,
+) {
+    for _ in add_event.iter()
+    {
+        let picked = picked_indices(&engine, &selected);
+        if picked.len() != 1
+        {
+            info!("Pick a single card to put on the centre row");
+            continue;
+        }
+        match apply_action(&mut engine, Action::AddToRow { seat: MAIN_SEAT, index: picked[0] }, &mut table_events)
+        {
+            Ok(()) => selected.0.clear(),
+            Err(err) => info!("{}", err),
+        }
+    }
+}
 
-        if !rules.clockwise
+fn call_last_card(
+    mut call_event: EventReader<CallLastCard>,
+    mut engine: ResMut<GameEngine>,
+    mut table_events: EventWriter<TableEvent>,
+) {
+    for _ in call_event.iter()
+    {
+        if let Err(err) = apply_action(&mut engine, Action::Call { seat: MAIN_SEAT }, &mut table_events)
         {
-            gameplay.player_turn = PlayerName::from_usize(1).unwrap();
+            info!("{}", err);
         }
-        else
+    }
+}
+
+fn catch_call(
+    mut catch_event: EventReader<CatchCall>,
+    mut engine: ResMut<GameEngine>,
+    mut table_events: EventWriter<TableEvent>,
+) {
+    for _ in catch_event.iter()
+    {
+        let Some(target) = engine.uncalled() else { continue; };
+        if let Err(err) = apply_action(&mut engine, Action::Catch { seat: MAIN_SEAT, target }, &mut table_events)
         {
-            gameplay.player_turn = PlayerName::from_usize(rules.num_players - 1).unwrap();
+            info!("{}", err);
         }
     }
 }
 
 fn bot_play(
-    mut commands: Commands,
-    mut discard_q: Query<(&mut Handle<Image>, &mut DiscardPile)>,
-    mut players_q: Query<(&mut Player, &PlayerName), Without<MainPlayer>>,
-    cards_image_q: Query<(Entity, &Id), (Without<DiscardPile>, Without<Deck>)>,
     time: Res<Time>,
-    rules: Res<Rules>,
     mut state: ResMut<BotWaiting>,
-    mut game_rules: ResMut<GameplayState>,
-    asset_server: Res<AssetServer>,
+    catch: Res<BotCatch>,
+    jump_in: Res<BotJumpIn>,
+    mut engine: ResMut<GameEngine>,
+    seed: Res<GameSeed>,
+    mut table_events: EventWriter<TableEvent>,
 ) {
-    if game_rules.player_turn == PlayerName::MainPlayer { return; }
+    // A move thought out for a table that changed since, by the main player or a catch, is
+    // thought out anew
+    if engine.turn() == MAIN_SEAT || engine.is_changed()
+    {
+        state.task = None;
+    }
+    if engine.turn() == MAIN_SEAT { return; }
+    // Moving on would let a missed call go, so bots give each other time to catch it
+    if catch.waiting(&engine) { return; }
+    // A card others could jump in on is left to them first, so moves come in the same order
+    if jump_in.waiting(&engine) { return; }
+
+    // Searching bots think off the main thread for a fixed number of rounds, so a seed takes
+    // the same moves on any machine
+    let search = SearchBot { budget: state.event_timer.duration(), rounds: Some(SEARCH_ROUNDS) };
+    state.delay_over |= state.event_timer.tick(time.delta()).finished();
+    let delay_over = state.delay_over;
+    let seat = engine.turn();
+    let task = state.task.get_or_insert_with(|| {
+        let table = engine.clone();
+        let mut bot_rng = move_rng(seed.round, table.history().len(), MoveChoice::Action);
+        AsyncComputeTaskPool::get().spawn(async move {
+            let view = BotView::new(&table, seat);
+            match table.rules().bot_levels[seat]
+            {
+                BotLevel::Master => search.action(&view, &mut bot_rng),
+                level => level.strategy().action(&view, &mut bot_rng),
+            }
+        })
+    });
+    if !delay_over { return; }
+    let Some(action) = task.now_or_never() else { return; };
+    state.task = None;
+    state.delay_over = false;
 
-    if state.event_timer.tick(time.delta()).finished()
+    match apply_action(&mut engine, action, &mut table_events)
     {
-        let (mut discard_image, mut pile) = discard_q.single_mut();
-        'players: for (mut player, name) in players_q.iter_mut()
+        Ok(()) => bot_call(&mut engine, seat, seed.round, &mut table_events),
+        Err(err) => warn!("{:?} of {:?}: {}", action, PlayerName::from_usize(seat).unwrap(), err),
+    }
+}
+
+fn bot_jump_in(
+    time: Res<Time>,
+    mut state: ResMut<BotJumpIn>,
+    mut engine: ResMut<GameEngine>,
+    seed: Res<GameSeed>,
+    mut table_events: EventWriter<TableEvent>,
+) {
+    if !engine.rules().jump_in { return; }
+
+    // Start waiting anew for every card put on the pile
+    let top_card = Some(engine.top_card().id);
+    if state.top_card != top_card
+    {
+        state.top_card = top_card;
+        state.timer.reset();
+    }
+    if !state.timer.tick(time.delta()).just_finished() { return; }
+
+    // A bot whose turn it is plays as usual
+    let jump_in = (0..engine.num_seats())
+        .filter(|&seat| seat != MAIN_SEAT && seat != engine.turn())
+        .find_map(|seat| engine.hand(seat).iter().position(|card| engine.can_jump_in(card)).map(|index| (seat, index)));
+    if let Some((seat, index)) = jump_in
+    {
+        match apply_action(&mut engine, Action::Play { seat, index }, &mut table_events)
         {
-            if game_rules.player_turn != *name { continue 'players; }
+            Ok(()) => bot_call(&mut engine, seat, seed.round, &mut table_events),
+            Err(err) => warn!("Jump in of {:?}: {}", PlayerName::from_usize(seat).unwrap(), err),
+        }
+    }
+}
+
+// Once a turn runs out in turbo mode the seat draws a card and the turn passes, whoever it is
+fn turn_countdown(
+    time: Res<Time>,
+    mut clock: ResMut<TurnClock>,
+    mut engine: ResMut<GameEngine>,
+    mut table_events: EventWriter<TableEvent>,
+) {
+    if !engine.rules().turbo { return; }
 
-            let card = player.cards.pop().unwrap();
-            let image_name = format!("{}_{}.png", card.suite.to_string(), card.rank.to_string());
+    if clock.turn != engine.turns()
+    {
+        *clock = TurnClock::new(&engine);
+    }
+    if !clock.timer.tick(time.delta()).just_finished() { return; }
 
-            'cards: for (entity, id) in &cards_image_q
-            {
-                if card.id != id.0 { continue 'cards; }
+    let seat = engine.turn();
+    if let Err(err) = apply_action(&mut engine, Action::TimeOut { seat }, &mut table_events)
+    {
+        warn!("Time out of {:?}: {}", PlayerName::from_usize(seat).unwrap(), err);
+    }
+    // A single player keeps the turn, which still starts over
+    *clock = TurnClock::new(&engine);
+}
+
+// The seat next in the direction of play after a missed call catches it, or the bot after
+// it if that is the main player
+fn bot_catch(
+    time: Res<Time>,
+    mut state: ResMut<BotCatch>,
+    mut engine: ResMut<GameEngine>,
+    mut table_events: EventWriter<TableEvent>,
+) {
+    if state.target != engine.uncalled()
+    {
+        state.target = engine.uncalled();
+        state.timer = Timer::new(Duration::from_millis(engine.rules().catch_delay as u64), TimerMode::Once);
+    }
+    let Some(target) = state.target else { return; };
+    if !state.timer.tick(time.delta()).just_finished() { return; }
+
+    let catcher = std::iter::successors(Some(engine.next_seat(target)), |&seat| Some(engine.next_seat(seat)))
+        .take_while(|&seat| seat != target)
+        .find(|&seat| seat != MAIN_SEAT);
+    if let Some(seat) = catcher
+    {
+        if let Err(err) = apply_action(&mut engine, Action::Catch { seat, target }, &mut table_events)
+        {
+            warn!("Catch by {:?}: {}", PlayerName::from_usize(seat).unwrap(), err);
+        }
+    }
+}
+
+// Move every card sprite to where the engine holds it: face up in a MainPlayer's hand, face down
+// in other hands. Sprites of cards that left hands are despawned, new ones are spawned
+#[allow(clippy::too_many_arguments)]
+fn sync_cards(
+    mut commands: Commands,
+    engine: Res<GameEngine>,
+    players_q: Query<(&Player, &PlayerName)>,
+    mut cards_q: Query<(Entity, &Id, &mut Handle<Image>, &mut Transform, &mut Sprite)>,
+    mut discard_q: Query<&mut Handle<Image>, (With<DiscardPile>, Without<Id>)>,
+    mut indicator_q: Query<(&mut Sprite, &mut Visibility), With<ColorIndicator>>,
+    mut penalty_q: Query<&mut Text, With<PenaltyText>>,
+    selected: Res<SelectedCards>,
+    asset_server: Res<AssetServer>,
+) {
+    let tex_back: Handle<Image> = asset_server.load("Back.png");
 
-                *discard_image = asset_server.load(image_name);
-                commands.entity(entity).despawn();
-                pile.cards.push(card);
-                break 'players;
+    let drawn = engine.drawn().filter(|_| engine.turn() == MAIN_SEAT).map(|card| card.id);
+
+    // Where every card inside hands has to be, by Id, and how it is tinted
+    let mut layout: HashMap<usize, (Handle<Image>, Transform, Color)> = HashMap::new();
+    for (player, name) in &players_q
+    {
+        let seat = *name as usize;
+        for (j, card) in engine.hand(seat).iter().enumerate()
+        {
+            let texture = if seat == MAIN_SEAT { asset_server.load(card_image_name(card)) } else { tex_back.clone() };
+            let mut slot = hand_slot(player, seat, j);
+            if seat == MAIN_SEAT && selected.0.contains(&card.id)
+            {
+                slot.translation.y += SELECTED_CARD_RAISE;
             }
+            let tint = if drawn == Some(card.id) { DRAWN_CARD_TINT } else { Color::WHITE };
+            layout.insert(card.id, (texture, slot, tint));
+        }
+    }
+    let row_len = engine.centre_row().len();
+    for (j, card) in engine.centre_row().iter().enumerate()
+    {
+        let transform = Transform::from_xyz(row_card_x(j, row_len), CENTRE_ROW_Y, j as f32).with_scale(ROW_CARD_SCALE);
+        layout.insert(card.id, (asset_server.load(card_image_name(card)), transform, Color::WHITE));
+    }
+
+    for (entity, id, mut image, mut pos, mut sprite) in cards_q.iter_mut()
+    {
+        match layout.remove(&id.0)
+        {
+            Some((texture, transform, tint)) => {
+                *image = texture;
+                *pos = transform;
+                sprite.color = tint;
+            },
+            None => commands.entity(entity).despawn(),
+        }
+    }
+
+    // New cards show up once show_landed_cards knows whether they are still flying in
+    for (id, (texture, transform, tint)) in layout
+    {
+        commands.spawn((
+            CardBundle {
+                sprite: SpriteBundle {
+                    sprite: Sprite { color: tint, ..default() },
+                    texture,
+                    transform,
+                    visibility: Visibility::Hidden,
+                    ..default()
+                },
+                id: Id(id),
+            },
+            GameItem,
+        ));
+    }
+
+    *discard_q.single_mut() = asset_server.load(card_image_name(engine.top_card()));
+
+    // Wild card on top is framed with the color it was given
+    let (mut indicator, mut visibility) = indicator_q.single_mut();
+    if engine.top_card().suite == Suit::Black && engine.color() != Suit::Black
+    {
+        indicator.color = suit_color(engine.color());
+        *visibility = Visibility::Visible;
+    }
+    else
+    {
+        *visibility = Visibility::Hidden;
+    }
+
+    penalty_q.single_mut().sections[0].value = match engine.penalty()
+    {
+        0 => String::new(),
+        count => format!("+{}", count),
+    };
+}
+
+// Every round is kept for the Replays menu once it is over or left for the menu
+fn record_replay(
+    engine: Option<Res<GameEngine>>,
+    seed: Res<GameSeed>,
+    scoreboard: Res<Scoreboard>,
+) {
+    let Some(engine) = engine else { return; };
+    if engine.history().is_empty() { return; }
+
+    let mut replays = match Replays::load()
+    {
+        Ok(replays) => replays,
+        Err(ReplayError::Io(err)) if err.kind() == std::io::ErrorKind::NotFound => Replays::default(),
+        // A file edited by hand is not written over
+        Err(err) => {
+            warn!("{}, the round is not recorded", err);
+            return;
+        },
+    };
+    replays.push(Replay::record(&engine, seed.round, seed.current, scoreboard.rounds() + 1));
+    if let Err(err) = replays.save()
+    {
+        warn!("{}", err);
+    }
+}
+
+// Results are shown as soon as somebody runs out of cards
+fn check_round_over(
+    engine: Res<GameEngine>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    if engine.winner().is_some()
+    {
+        next_state.set(GameState::RoundOver);
+    }
+}
+
+// A few card backs fly from a discard pile to a deck that was refilled from it
+fn animate_reshuffle(
+    mut commands: Commands,
+    mut table_events: EventReader<TableEvent>,
+    asset_server: Res<AssetServer>,
+) {
+    for event in table_events.iter()
+    {
+        let engine::Event::DeckReshuffled { count } = event.0 else { continue; };
+        for i in 0..count.min(RESHUFFLE_ANIMATION_CARDS)
+        {
+            let delay = i as f32 * RESHUFFLE_CARD_DELAY;
+            let from = Vec3::new(DECK_DISCARD_DISTANCE, 0.0, 3.0 + i as f32);
+            commands.spawn((
+                FlyingCard {
+                    from,
+                    to: Vec3::new(-DECK_DISCARD_DISTANCE, 0.0, 3.0 + i as f32),
+                    delay,
+                    timer: Timer::from_seconds(delay + RESHUFFLE_FLIGHT_TIME, TimerMode::Once),
+                    card: None,
+                },
+                SpriteBundle {
+                    texture: asset_server.load("Back.png"),
+                    transform: Transform::from_translation(from).with_scale(DECK_CARD_SCALE),
+                    ..default()
+                },
+                GameItem,
+            ));
+        }
+    }
+}
+
+// Every drawn card flies from a deck to its place in a hand, one after another
+fn animate_draws(
+    mut commands: Commands,
+    mut table_events: EventReader<TableEvent>,
+    engine: Res<GameEngine>,
+    players_q: Query<(&Player, &PlayerName)>,
+    asset_server: Res<AssetServer>,
+) {
+    let mut delay = 0.0;
+    for event in table_events.iter()
+    {
+        let engine::Event::CardDrawn { seat, card } = event.0 else { continue; };
+        // Cards that already left a hand again have nowhere to fly
+        let Some(j) = engine.hand(seat).iter().position(|held| held.id == card.id) else { continue; };
+        let Some((player, _)) = players_q.iter().find(|(_, name)| **name as usize == seat) else { continue; };
+
+        let slot = hand_slot(player, seat, j);
+        let from = Vec3::new(-DECK_DISCARD_DISTANCE, 0.0, 50.0);
+        let texture = if seat == MAIN_SEAT { asset_server.load(card_image_name(&card)) } else { asset_server.load("Back.png") };
+        commands.spawn((
+            FlyingCard {
+                from,
+                to: slot.translation.truncate().extend(from.z),
+                delay,
+                timer: Timer::from_seconds(delay + DRAW_FLIGHT_TIME, TimerMode::Once),
+                card: Some(card.id),
+            },
+            SpriteBundle {
+                texture,
+                transform: Transform::from_translation(from).with_scale(slot.scale),
+                visibility: Visibility::Hidden,
+                ..default()
+            },
+            GameItem,
+        ));
+        delay += DRAW_CARD_DELAY;
+    }
+}
+
+fn move_flying_cards(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut cards_q: Query<(Entity, &mut FlyingCard, &mut Transform, &mut Visibility)>,
+) {
+    for (entity, mut card, mut transform, mut visibility) in cards_q.iter_mut()
+    {
+        if card.timer.tick(time.delta()).finished()
+        {
+            commands.entity(entity).despawn();
+            continue;
         }
+        // Waiting cards are shown only once they take off
+        let flight_time = card.timer.duration().as_secs_f32() - card.delay;
+        let progress = ((card.timer.elapsed_secs() - card.delay) / flight_time).clamp(0.0, 1.0);
+        transform.translation = card.from.lerp(card.to, progress);
+        if card.timer.elapsed_secs() >= card.delay && *visibility == Visibility::Hidden
+        {
+            *visibility = Visibility::Inherited;
+        }
+    }
+}
 
-        let index = game_rules.player_turn as usize;
-        let index = if rules.clockwise { index - 1 } else { (index + 1) % rules.num_players };
-        game_rules.player_turn = PlayerName::from_usize(index).unwrap();
+// Cards in hands are hidden while a copy of them is still flying in
+fn show_landed_cards(
+    flying_q: Query<&FlyingCard>,
+    mut cards_q: Query<(&Id, &mut Visibility)>,
+) {
+    for (id, mut visibility) in cards_q.iter_mut()
+    {
+        let flying = flying_q.iter().any(|card| card.card == Some(id.0));
+        let shown = if flying { Visibility::Hidden } else { Visibility::Inherited };
+        if *visibility != shown
+        {
+            *visibility = shown;
+        }
     }
 }
 
@@ -582,38 +1149,32 @@ fn mouse_pressed(mouse_button_input: Res<Input<MouseButton>>) -> bool
     mouse_button_input.just_pressed(MouseButton::Left)
 }
 
+fn match_over(rules: Res<Rules>, scoreboard: Res<Scoreboard>) -> bool
+{
+    scoreboard.match_winner(rules.target_score).is_some()
+}
+
+// Card sprites follow an engine in a game and while a replay is played back
+fn on_table(state: Res<State<GameState>>) -> bool
+{
+    matches!(state.0, GameState::Game | GameState::Replay)
+}
+
 fn test(
-    game_rules: Res<GameplayState>,
+    engine: Res<GameEngine>,
     rules: Res<Rules>,
-    discard_pile_q: Query<&DiscardPile>,
-    deck_q: Query<&Deck>,
-    id_q: Query<&Id>,
     key: Res<Input<KeyCode>>,
-    window_q: Query<&Window>,
 ) {
-    let window = window_q.single();
-
     if key.just_pressed(KeyCode::D) {
-        info!("GameState: {:?}\nRules: {:?}", game_rules, rules);
+        info!("GameState: {:?}\nRules: {:?}", engine, rules);
     }
 
     if key.just_pressed(KeyCode::T) {
-        info!("{:?}", game_rules.player_turn);
-    }
-
-    if key.just_pressed(KeyCode::R) {
-        let deck = deck_q.single();
-        let num_cards = deck.cards.len();
-        info!("Cards in deck left: {}", num_cards);
+        info!("{:?}", PlayerName::from_usize(engine.turn()).unwrap());
     }
 
-
     if key.just_pressed(KeyCode::Space) {
-        let pile = discard_pile_q.single();
-        if let Some(last_card) = pile.cards.last()
-        {
-            info!("{:?}", last_card);
-        }
+        info!("{:?}", engine.top_card());
     }
 }
 
@@ -630,24 +1191,136 @@ fn get_card_index(left: f32, x: f32, counter: usize) -> usize {
     counter - 1
 }
 
-// Allows to format an enum into string
-impl fmt::Display for Rank {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        fmt::Debug::fmt(self, f)
+pub fn card_image_name(card: &Card) -> String {
+    format!("{}_{}.png", card.suite, card.rank)
+}
+
+// Name shown above a seat's hand and in results
+pub fn seat_name(seat: usize) -> String {
+    if seat == MAIN_SEAT { "Main Player".to_string() } else { format!("Player {}", seat + 1) }
+}
+
+// Colors of the card images
+pub fn suit_color(suit: Suit) -> Color {
+    match suit {
+        Suit::Red => Color::rgb_u8(239, 71, 111),
+        Suit::Blue => Color::rgb_u8(17, 138, 178),
+        Suit::Yellow => Color::rgb_u8(255, 209, 102),
+        Suit::Green => Color::rgb_u8(6, 214, 160),
+        Suit::Black => Color::rgb_u8(45, 45, 60),
     }
 }
 
-impl fmt::Display for Suit {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        fmt::Debug::fmt(self, f)
+// Centre row is laid out around the middle of a table
+fn row_card_x(j: usize, row_len: usize) -> f32 {
+    (j as f32 - (row_len as f32 - 1.0) / 2.0) * CENTRE_ROW_SPACING
+}
+
+// Where a card `j` of a hand of `seat` lies. Only a MainPlayer gets big cards spread apart
+fn hand_slot(player: &Player, seat: usize, j: usize) -> Transform {
+    let (spacing, scale) = if seat == MAIN_SEAT { (PLAYER_CARDS_SPACING, PLAYER_CARD_SCALE) } else { (ENEMY_CARDS_SPACING, ENEMY_CARD_SCALE) };
+    Transform::from_xyz(player.pos.x + (j as f32) * spacing, player.pos.y, j as f32).with_scale(scale)
+}
+
+// Apply an action of a seat and let other systems know what it has caused
+fn apply_action(engine: &mut GameEngine, action: Action, table_events: &mut EventWriter<TableEvent>) -> Result<(), RuleError> {
+    let events = engine.apply(action)?;
+    table_events.send_batch(events.into_iter().map(TableEvent));
+    Ok(())
+}
+
+// Right after getting down to the last cards a bot calls them, unless it forgets
+fn bot_call(engine: &mut GameEngine, seat: usize, seed: u64, table_events: &mut EventWriter<TableEvent>) {
+    let mut rng = move_rng(seed, engine.history().len(), MoveChoice::Call);
+    if engine.uncalled() != Some(seat) || rng.gen_bool(BOT_FORGET_CALL_CHANCE) { return; }
+    if let Err(err) = apply_action(engine, Action::Call { seat }, table_events)
+    {
+        warn!("Call of {:?}: {}", PlayerName::from_usize(seat).unwrap(), err);
+    }
+}
+
+// Hand indices of the picked cards, in order of picking. Cards that left the hand are left out
+fn picked_indices(engine: &GameEngine, selected: &SelectedCards) -> Vec<usize> {
+    let hand = engine.hand(MAIN_SEAT);
+    selected.0.iter().filter_map(|&id| hand.iter().position(|card| card.id == id)).collect()
+}
+
+impl Default for BotJumpIn {
+    fn default() -> Self {
+        BotJumpIn {
+            top_card: None,
+            timer: Timer::from_seconds(BOT_JUMP_IN_DELAY, TimerMode::Once),
+        }
+    }
+}
+
+impl TurnClock
+{
+    fn new(engine: &GameEngine) -> Self
+    {
+        let timer = Timer::from_seconds(engine.rules().turn_time as f32, TimerMode::Once);
+        TurnClock { seat: engine.turn(), turn: engine.turns(), timer }
+    }
+
+    pub fn seat(&self) -> usize { self.seat }
+
+    // Whole seconds left, counting the one that is running
+    pub fn seconds_left(&self) -> u32
+    {
+        self.timer.remaining_secs().ceil() as u32
+    }
+}
+
+impl Default for TurnClock {
+    fn default() -> Self {
+        let timer = Timer::from_seconds(Rules::default().turn_time as f32, TimerMode::Once);
+        TurnClock { seat: MAIN_SEAT, turn: 0, timer }
+    }
+}
+
+impl BotJumpIn
+{
+    // A bot other than the one whose turn it is can still jump in on the top card
+    fn waiting(&self, engine: &GameEngine) -> bool
+    {
+        if !engine.rules().jump_in { return false; }
+        let can_jump_in = (0..engine.num_seats())
+            .filter(|&seat| seat != MAIN_SEAT && seat != engine.turn())
+            .any(|seat| engine.hand(seat).iter().any(|card| engine.can_jump_in(card)));
+        can_jump_in && (self.top_card != Some(engine.top_card().id) || !self.timer.finished())
+    }
+}
+
+impl BotCatch
+{
+    // A missed call is still waiting for bots to notice it
+    fn waiting(&self, engine: &GameEngine) -> bool
+    {
+        engine.uncalled().is_some() && (self.target != engine.uncalled() || !self.timer.finished())
+    }
+}
+
+impl Default for BotCatch {
+    fn default() -> Self {
+        BotCatch {
+            target: None,
+            timer: Timer::from_seconds(0.0, TimerMode::Once),
+        }
+    }
+}
+
+impl Default for GameRng {
+    fn default() -> Self {
+        GameRng(StdRng::from_entropy())
     }
 }
 
 impl Default for BotWaiting {
     fn default() -> Self {
         BotWaiting {
-            event_timer: Timer::from_seconds(1.0, TimerMode::Repeating),
+            event_timer: Timer::from_seconds(BOT_DELAY, TimerMode::Repeating),
+            task: None,
+            delay_over: false,
         }
     }
 }
-
@@ -1,13 +1,50 @@
 use bevy::prelude::*;
 use crate::{GameState, Rules};
-use crate::game::{GameItem, GameplayState, PlayerName};
+use crate::engine::{Event, GameEngine, Scoreboard, Suit, CATCH_PENALTY, COLORS};
+use crate::game::{seat_name, suit_color, AddToRow, CallLastCard, CatchCall, ChallengeDraw4, ChooseColor, DrawCard, GameItem, GameSeed, SkipTurn, SwapWith, TableEvent, TurnClock, MAIN_SEAT};
 use crate::menu::{TEXT_COLOR, NORMAL_BUTTON, PRESSED_BUTTON, HOVERED_BUTTON};
-use num::FromPrimitive;         //access enum values via integer
+
+// Seconds a message about something that happened on a table stays on screen
+const HUD_MESSAGE_TIME: f32 = 2.0;
 
 #[derive(Component)]
 enum InGameButtonAction {
     Skip,
     Menu,
+    Color(Suit),
+    Challenge,
+    AcceptDraw4,
+    SwapWith(usize),
+    AddToRow,
+    Call,
+    Catch,
+}
+
+// Tag component of buttons to pick a color after the main player has played a wild card
+#[derive(Component)]
+struct ColorPicker;
+
+// Tag component of buttons to answer a Wild Draw 4 played on the main player
+#[derive(Component)]
+struct ChallengePrompt;
+
+// Tag component of buttons to pick whom to swap hands with after the main player has played a Seven
+#[derive(Component)]
+struct SwapPicker;
+
+// Tag component of a turbo mode countdown of a current turn
+#[derive(Component)]
+struct TurnClockText;
+
+// Tag component of a button to catch a seat that missed its call
+#[derive(Component)]
+struct CatchPrompt(usize);
+
+// Short notice about something that happened on a table, cleared once `timer` is up
+#[derive(Component)]
+struct HudMessage
+{
+    timer: Timer,
 }
 
 #[derive(Default)]
@@ -21,7 +58,10 @@ impl Plugin for GameUIPlugin
     {
         app.add_event::<GoMenu>()
             .add_system(ui_setup.in_schedule(OnEnter(GameState::Game)))
-            .add_systems((keyboard_action, ui_button_action, button_colors, go_to_menu).in_set(OnUpdate(GameState::Game)));
+            .add_systems((keyboard_action, ui_button_action, button_colors, go_to_menu).in_set(OnUpdate(GameState::Game)))
+            .add_systems((show_table_events, hide_hud_message).in_set(OnUpdate(GameState::Game)))
+            .add_system(show_turn_clock.run_if(resource_changed::<TurnClock>()).in_set(OnUpdate(GameState::Game)))
+            .add_system(show_prompts.run_if(resource_exists::<GameEngine>().and_then(resource_changed::<GameEngine>())).in_set(OnUpdate(GameState::Game)));
     }
 }
 
@@ -29,7 +69,96 @@ fn ui_setup(
     mut commands: Commands,
     asset_server: Res<AssetServer>,
     rules: Res<Rules>,
+    scoreboard: Res<Scoreboard>,
+    seed: Res<GameSeed>,
 ) {
+    // Scores only change between rounds, and the HUD is built anew for every round
+    let score_style = TextStyle {
+        font: asset_server.load("fonts/FiraSans-Bold.ttf"),
+        font_size: 24.0,
+        color: TEXT_COLOR,
+    };
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    position: UiRect {
+                        right: Val::Percent(2.0),
+                        top: Val::Percent(3.0),
+                        ..default()
+                    },
+                    flex_direction: FlexDirection::Column,
+                    align_items: AlignItems::FlexEnd,
+                    ..default()
+                },
+                ..default()
+            },
+            GameItem,
+        ))
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section(
+                format!("Round {} - to {} points", scoreboard.rounds() + 1, rules.target_score),
+                score_style.clone(),
+            ));
+            for (seat, score) in scoreboard.scores().iter().enumerate()
+            {
+                parent.spawn(TextBundle::from_section(
+                    format!("{}: {}", seat_name(seat), score),
+                    score_style.clone(),
+                ));
+            }
+            // To be put in a bug report, the match can be played again with it
+            parent.spawn(TextBundle::from_section(format!("Seed {}", seed.current), score_style.clone()));
+        });
+
+    commands.spawn((
+        TextBundle::from_section(
+            "",
+            TextStyle {
+                font: asset_server.load("fonts/Vividly.otf"),
+                font_size: 50.0,
+                color: TEXT_COLOR,
+            }
+        )
+        .with_style(Style {
+            position_type: PositionType::Absolute,
+            position: UiRect {
+                left: Val::Percent(40.0),
+                top: Val::Percent(22.0),
+                ..default()
+            },
+            ..default()
+        }),
+        HudMessage { timer: Timer::from_seconds(HUD_MESSAGE_TIME, TimerMode::Once) },
+        GameItem,
+    ));
+
+    if rules.turbo
+    {
+        commands.spawn((
+            TextBundle::from_section(
+                "",
+                TextStyle {
+                    font: asset_server.load("fonts/FiraSans-Bold.ttf"),
+                    font_size: 36.0,
+                    color: TEXT_COLOR,
+                }
+            )
+            .with_style(Style {
+                position_type: PositionType::Absolute,
+                position: UiRect {
+                    left: Val::Percent(3.0),
+                    top: Val::Percent(22.0),
+                    ..default()
+                },
+                ..default()
+            }),
+            TurnClockText,
+            GameItem,
+        ));
+    }
+
     commands
         .spawn((
             ButtonBundle {
@@ -70,7 +199,95 @@ fn ui_setup(
             ));
         });
 
-    if rules.no_skip || rules.num_players == 1 { return; }
+    if rules.last_call
+    {
+        commands
+            .spawn((
+                ButtonBundle {
+                    style: Style {
+                        size: Size::new(Val::Px(160.0), Val::Px(70.0)),
+                        position: UiRect {
+                            left: Val::Percent(80.0),
+                            top: Val::Percent(85.0),
+                            ..default()
+                        },
+                        justify_content: JustifyContent::Center,
+                        flex_direction: FlexDirection::Column,
+                        align_items: AlignItems::Center,
+                        ..default()
+                    },
+                    background_color: NORMAL_BUTTON.into(),
+                    ..default()
+                },
+                InGameButtonAction::Call,
+                GameItem,
+            ))
+            .with_children(|parent| {
+                parent.spawn(TextBundle::from_section(
+                    if rules.dos { "Dos!" } else { "Uno!" },
+                    TextStyle {
+                        font: asset_server.load("fonts/Vividly.otf"),
+                        font_size: 40.0,
+                        color: TEXT_COLOR,
+                    }
+                ));
+                parent.spawn(TextBundle::from_section(
+                    "[ u ]",
+                    TextStyle {
+                        font: asset_server.load("fonts/FiraSans-Bold.ttf"),
+                        font_size: 20.0,
+                        color: TEXT_COLOR,
+                    }
+                ));
+            });
+    }
+
+    // In DOS mode picked cards go on the centre row with a button
+    if rules.dos
+    {
+        commands
+            .spawn((
+                ButtonBundle {
+                    style: Style {
+                        size: Size::new(Val::Px(160.0), Val::Px(70.0)),
+                        position: UiRect {
+                            left: Val::Percent(10.0),
+                            top: Val::Percent(74.0),
+                            ..default()
+                        },
+                        justify_content: JustifyContent::Center,
+                        flex_direction: FlexDirection::Column,
+                        align_items: AlignItems::Center,
+                        ..default()
+                    },
+                    background_color: NORMAL_BUTTON.into(),
+                    ..default()
+                },
+                InGameButtonAction::AddToRow,
+                GameItem,
+            ))
+            .with_children(|parent| {
+                parent.spawn(TextBundle::from_section(
+                    "To row",
+                    TextStyle {
+                        font: asset_server.load("fonts/Vividly.otf"),
+                        font_size: 40.0,
+                        color: TEXT_COLOR,
+                    }
+                ));
+                parent.spawn(TextBundle::from_section(
+                    "[ r ]",
+                    TextStyle {
+                        font: asset_server.load("fonts/FiraSans-Bold.ttf"),
+                        font_size: 20.0,
+                        color: TEXT_COLOR,
+                    }
+                ));
+            });
+    }
+
+    // A DOS turn always ends with a button, after matching
+    if !rules.dos && (rules.no_skip || rules.num_players == 1) { return; }
 
     commands
         .spawn((
@@ -113,10 +330,271 @@ fn ui_setup(
         });
 }
 
+// Spawn or remove choices that the main player has to make before the game can go on
+fn show_prompts(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    engine: Res<GameEngine>,
+    picker_q: Query<Entity, With<ColorPicker>>,
+    challenge_q: Query<Entity, With<ChallengePrompt>>,
+    swap_q: Query<Entity, With<SwapPicker>>,
+    catch_q: Query<(Entity, &CatchPrompt)>,
+) {
+    let my_turn = engine.turn() == MAIN_SEAT;
+    let prompt_style = Style {
+        position_type: PositionType::Absolute,
+        position: UiRect {
+            left: Val::Percent(38.0),
+            top: Val::Percent(58.0),
+            ..default()
+        },
+        ..default()
+    };
+    let button_style = Style {
+        size: Size::new(Val::Px(70.0), Val::Px(70.0)),
+        margin: UiRect::all(Val::Px(8.0)),
+        justify_content: JustifyContent::Center,
+        align_items: AlignItems::Center,
+        ..default()
+    };
+
+    match (my_turn && engine.choosing_color(), picker_q.get_single())
+    {
+        (true, Err(_)) => {
+            commands
+                .spawn((NodeBundle { style: prompt_style.clone(), ..default() }, ColorPicker, GameItem))
+                .with_children(|parent| {
+                    for color in COLORS
+                    {
+                        parent
+                            .spawn((
+                                ButtonBundle {
+                                    style: button_style.clone(),
+                                    background_color: NORMAL_BUTTON.into(),
+                                    ..default()
+                                },
+                                InGameButtonAction::Color(color),
+                            ))
+                            // Button itself is recolored on hover, so the color goes on a child
+                            .with_children(|parent| {
+                                parent.spawn(NodeBundle {
+                                    style: Style {
+                                        size: Size::new(Val::Px(54.0), Val::Px(54.0)),
+                                        ..default()
+                                    },
+                                    background_color: suit_color(color).into(),
+                                    ..default()
+                                });
+                            });
+                    }
+                });
+        },
+        (false, Ok(entity)) => commands.entity(entity).despawn_recursive(),
+        _ => (),
+    }
+
+    match (my_turn && engine.challenge_pending() && !engine.choosing_color(), challenge_q.get_single())
+    {
+        (true, Err(_)) => {
+            commands
+                .spawn((NodeBundle { style: prompt_style, ..default() }, ChallengePrompt, GameItem))
+                .with_children(|parent| {
+                    for (label, action) in [("Challenge", InGameButtonAction::Challenge), ("Draw 4", InGameButtonAction::AcceptDraw4)]
+                    {
+                        parent
+                            .spawn((
+                                ButtonBundle {
+                                    style: Style {
+                                        size: Size::new(Val::Px(160.0), Val::Px(70.0)),
+                                        ..button_style.clone()
+                                    },
+                                    background_color: NORMAL_BUTTON.into(),
+                                    ..default()
+                                },
+                                action,
+                            ))
+                            .with_children(|parent| {
+                                parent.spawn(TextBundle::from_section(
+                                    label,
+                                    TextStyle {
+                                        font: asset_server.load("fonts/Vividly.otf"),
+                                        font_size: 40.0,
+                                        color: TEXT_COLOR,
+                                    }
+                                ));
+                            });
+                    }
+                });
+        },
+        (false, Ok(entity)) => commands.entity(entity).despawn_recursive(),
+        _ => (),
+    }
+
+    match (my_turn && engine.choosing_swap(), swap_q.get_single())
+    {
+        (true, Err(_)) => {
+            commands
+                .spawn((
+                    NodeBundle {
+                        style: Style {
+                            position_type: PositionType::Absolute,
+                            position: UiRect {
+                                left: Val::Percent(20.0),
+                                top: Val::Percent(52.0),
+                                ..default()
+                            },
+                            size: Size::new(Val::Percent(60.0), Val::Auto),
+                            flex_wrap: FlexWrap::Wrap,
+                            justify_content: JustifyContent::Center,
+                            ..default()
+                        },
+                        ..default()
+                    },
+                    SwapPicker,
+                    GameItem,
+                ))
+                .with_children(|parent| {
+                    for seat in (0..engine.num_seats()).filter(|&seat| seat != MAIN_SEAT)
+                    {
+                        parent
+                            .spawn((
+                                ButtonBundle {
+                                    style: Style {
+                                        size: Size::new(Val::Px(170.0), Val::Px(50.0)),
+                                        ..button_style.clone()
+                                    },
+                                    background_color: NORMAL_BUTTON.into(),
+                                    ..default()
+                                },
+                                InGameButtonAction::SwapWith(seat),
+                            ))
+                            .with_children(|parent| {
+                                parent.spawn(TextBundle::from_section(
+                                    format!("{} ({})", seat_name(seat), engine.hand(seat).len()),
+                                    TextStyle {
+                                        font: asset_server.load("fonts/FiraSans-Bold.ttf"),
+                                        font_size: 22.0,
+                                        color: TEXT_COLOR,
+                                    }
+                                ));
+                            });
+                    }
+                });
+        },
+        (false, Ok(entity)) => commands.entity(entity).despawn_recursive(),
+        _ => (),
+    }
+
+    // Any seat but the main player can be caught, the prompt follows whoever it is
+    let uncalled = engine.uncalled().filter(|&seat| seat != MAIN_SEAT);
+    if let Ok((entity, prompt)) = catch_q.get_single()
+    {
+        if uncalled == Some(prompt.0) { return; }
+        commands.entity(entity).despawn_recursive();
+    }
+    let Some(seat) = uncalled else { return; };
+    commands
+        .spawn((
+            ButtonBundle {
+                style: Style {
+                    size: Size::new(Val::Px(240.0), Val::Px(70.0)),
+                    position: UiRect {
+                        left: Val::Percent(80.0),
+                        top: Val::Percent(74.0),
+                        ..default()
+                    },
+                    justify_content: JustifyContent::Center,
+                    flex_direction: FlexDirection::Column,
+                    align_items: AlignItems::Center,
+                    ..default()
+                },
+                background_color: NORMAL_BUTTON.into(),
+                ..default()
+            },
+            InGameButtonAction::Catch,
+            CatchPrompt(seat),
+            GameItem,
+        ))
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section(
+                format!("Catch {}!", seat_name(seat)),
+                TextStyle {
+                    font: asset_server.load("fonts/Vividly.otf"),
+                    font_size: 34.0,
+                    color: TEXT_COLOR,
+                }
+            ));
+            parent.spawn(TextBundle::from_section(
+                "[ c ]",
+                TextStyle {
+                    font: asset_server.load("fonts/FiraSans-Bold.ttf"),
+                    font_size: 20.0,
+                    color: TEXT_COLOR,
+                }
+            ));
+        });
+}
+
+fn show_table_events(
+    mut table_events: EventReader<TableEvent>,
+    mut message_q: Query<(&mut Text, &mut HudMessage)>,
+    rules: Res<Rules>,
+) {
+    for event in table_events.iter()
+    {
+        let message = match event.0
+        {
+            Event::DeckReshuffled { count } => format!("Deck reshuffled: {} cards", count),
+            Event::HandsSwapped { seat, with } => format!("{} swapped hands with {}", seat_name(seat), seat_name(with)),
+            Event::HandsRotated { .. } => "Hands passed on".to_string(),
+            Event::JumpedIn { seat } => format!("{} jumped in!", seat_name(seat)),
+            Event::Matched { seat, second: Some(_), color_match: true, .. } => format!("{}: double color match!", seat_name(seat)),
+            Event::Matched { seat, color_match: true, .. } => format!("{}: color match", seat_name(seat)),
+            Event::Called { seat } => format!("{}: {}", seat_name(seat), if rules.dos { "DOS!" } else { "UNO!" }),
+            Event::TimedOut { seat } => format!("{}: time is up", seat_name(seat)),
+            Event::Caught { seat, target } => format!("{} caught {}: +{}", seat_name(seat), seat_name(target), CATCH_PENALTY),
+            _ => continue,
+        };
+        let (mut text, mut hud) = message_q.single_mut();
+        text.sections[0].value = message;
+        hud.timer.reset();
+    }
+}
+
+fn show_turn_clock(
+    clock: Res<TurnClock>,
+    mut clock_q: Query<&mut Text, With<TurnClockText>>,
+) {
+    for mut text in clock_q.iter_mut()
+    {
+        text.sections[0].value = format!("{}: {}s", seat_name(clock.seat()), clock.seconds_left());
+    }
+}
+
+fn hide_hud_message(
+    time: Res<Time>,
+    mut message_q: Query<(&mut Text, &mut HudMessage)>,
+) {
+    for (mut text, mut hud) in message_q.iter_mut()
+    {
+        if hud.timer.tick(time.delta()).just_finished()
+        {
+            text.sections[0].value.clear();
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
 fn ui_button_action(
     interaction_q: Query<(&Interaction, &InGameButtonAction), (Changed<Interaction>, With<Button>)>,
-    rules: Res<Rules>,
-    mut gameplay_rules: ResMut<GameplayState>,
+    mut skip_event: EventWriter<SkipTurn>,
+    mut color_event: EventWriter<ChooseColor>,
+    mut challenge_event: EventWriter<ChallengeDraw4>,
+    mut draw_event: EventWriter<DrawCard>,
+    mut swap_event: EventWriter<SwapWith>,
+    mut row_event: EventWriter<AddToRow>,
+    mut call_event: EventWriter<CallLastCard>,
+    mut catch_event: EventWriter<CatchCall>,
     mut menu_event: EventWriter<GoMenu>,
 ) {
     for (interaction, button_action) in &interaction_q
@@ -125,15 +603,15 @@ fn ui_button_action(
         {
             match button_action
             {
-                InGameButtonAction::Skip => {
-                    if !gameplay_rules.player_drawn_card { return; }
-
-                    gameplay_rules.player_drawn_card = false;
-
-                    if !rules.clockwise { gameplay_rules.player_turn = PlayerName::from_usize(1).unwrap(); }
-                    else { gameplay_rules.player_turn = PlayerName::from_usize(rules.num_players - 1).unwrap(); }
-                }
+                InGameButtonAction::Skip => skip_event.send_default(),
                 InGameButtonAction::Menu => menu_event.send_default(),
+                InGameButtonAction::Color(suit) => color_event.send(ChooseColor(*suit)),
+                InGameButtonAction::Challenge => challenge_event.send_default(),
+                InGameButtonAction::AcceptDraw4 => draw_event.send_default(),
+                InGameButtonAction::SwapWith(seat) => swap_event.send(SwapWith(*seat)),
+                InGameButtonAction::AddToRow => row_event.send_default(),
+                InGameButtonAction::Call => call_event.send_default(),
+                InGameButtonAction::Catch => catch_event.send_default(),
             }
         }
     }
@@ -141,8 +619,10 @@ fn ui_button_action(
 
 fn keyboard_action(
     key: Res<Input<KeyCode>>,
-    rules: Res<Rules>,
-    mut gameplay_rules: ResMut<GameplayState>,
+    mut skip_event: EventWriter<SkipTurn>,
+    mut row_event: EventWriter<AddToRow>,
+    mut call_event: EventWriter<CallLastCard>,
+    mut catch_event: EventWriter<CatchCall>,
     mut menu_event: EventWriter<GoMenu>,
 ) {
     if key.just_pressed(KeyCode::Escape)
@@ -150,21 +630,28 @@ fn keyboard_action(
         menu_event.send_default();
     }
 
-    if key.just_pressed(KeyCode::Space) && gameplay_rules.player_drawn_card
+    if key.just_pressed(KeyCode::Space)
     {
-        gameplay_rules.player_drawn_card = false;
-        if !rules.clockwise
-        {
-            gameplay_rules.player_turn = PlayerName::from_usize(1).unwrap();
-        }
-        else
-        {
-            gameplay_rules.player_turn = PlayerName::from_usize(rules.num_players - 1).unwrap();
-        }
+        skip_event.send_default();
+    }
+
+    if key.just_pressed(KeyCode::R)
+    {
+        row_event.send_default();
+    }
+
+    if key.just_pressed(KeyCode::U)
+    {
+        call_event.send_default();
+    }
+
+    if key.just_pressed(KeyCode::C)
+    {
+        catch_event.send_default();
     }
 }
 
-fn button_colors(
+pub fn button_colors(
     mut interaction_query: Query<(&Interaction, &mut BackgroundColor), (Changed<Interaction>, With<Button>)>
 ) {
     for (interaction, mut color) in &mut interaction_query {
@@ -1,40 +1,57 @@
-#![feature(variant_count)]
 mod menu;
 mod game;
 mod fullscreen;
 mod game_ui;
+mod round_over;
+mod playback;
+mod settings;
+mod storage;
+pub mod engine;
+pub mod bots;
+pub mod search;
+pub mod presets;
+pub mod replays;
+pub mod simulation;
 
 use crate::menu::MenuPlugin;
 use crate::game::GamePlugin;
 use crate::game_ui::GameUIPlugin;
+use crate::round_over::RoundOverPlugin;
+use crate::playback::PlaybackPlugin;
+use crate::settings::SettingsPlugin;
 use crate::fullscreen::FullViewportPlugin;
+use crate::engine::{Rules, Scoreboard};
+use crate::presets::RulePresets;
+use crate::replays::Replays;
 
 use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
 
 #[derive(Clone, Copy, Default, Eq, PartialEq, Debug, Hash, States)]
 enum GameState {
     #[default]
     Menu,
     Game,
+    // Somebody has no cards left, results are shown over a table
+    RoundOver,
+    // A recorded round is played back on a table, nobody can act
+    Replay,
 }
 
 // One of the two settings that can be set through the menu. It will be a resource in the app
-#[derive(Resource, Debug, Component, PartialEq, Eq, Clone, Copy)]
+#[derive(Resource, Debug, Component, PartialEq, Eq, Clone, Copy, Default, Serialize, Deserialize)]
 enum DisplayQuality {
+    #[default]
     Light,
     Dark,
     Avocado,
 }
 
-#[derive(Resource, Debug, PartialEq, Eq, Clone, Copy)]
-struct Rules
-{
-    num_players: usize,
-    stackable_cards: bool,
-    turbo: bool,
-    clockwise: bool,
-    no_skip: bool,
-}
+// Rules are defined next to the engine that enforces them, which doesn't depend on Bevy
+impl Resource for Rules {}
+impl Resource for Scoreboard {}
+impl Resource for RulePresets {}
+impl Resource for Replays {}
 
 pub struct MainPlugin;
 
@@ -43,18 +60,14 @@ impl Plugin for MainPlugin
     fn build(&self, app: &mut App)
     {
         app.add_state::<GameState>()
-            .insert_resource(Rules {
-                num_players: 5,
-                stackable_cards: false,
-                turbo: false,
-                clockwise: false,
-                no_skip: false,
-            })
-            .insert_resource(DisplayQuality::Light)
+            .init_resource::<Scoreboard>()
+            .add_plugin(SettingsPlugin)
             .add_startup_system(setup)
             .add_plugin(MenuPlugin)
             .add_plugin(GamePlugin)
-            .add_plugin(GameUIPlugin);
+            .add_plugin(GameUIPlugin)
+            .add_plugin(RoundOverPlugin)
+            .add_plugin(PlaybackPlugin);
 
             #[cfg(target_family = "wasm")]
             app.add_plugin(FullViewportPlugin);
@@ -66,6 +79,9 @@ fn setup(mut commands: Commands)
     commands.spawn(Camera2dBundle::default());
 }
 
+// Filter for queries of buttons whose interaction changed this frame
+type ButtonInteraction = (Changed<Interaction>, With<Button>);
+
 // Generic system that takes a component as a parameter, and will despawn all entities with that component
 fn despawn_screen<T: Component>(to_despawn: Query<Entity, With<T>>, mut commands: Commands) {
     for entity in &to_despawn {
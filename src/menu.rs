@@ -1,14 +1,40 @@
 use bevy::prelude::*;
-use crate::{despawn_screen, GameState, DisplayQuality, Rules};
+use crate::{despawn_screen, ButtonInteraction, GameState, DisplayQuality, Rules};
+use crate::engine::{AfterDraw, MAX_PLAYERS, MIN_PENALTY_CAP};
+use crate::bots::BotLevel;
+use crate::presets::{RulePresets, PresetError};
+use crate::game::GameSeed;
+use crate::playback::Playback;
+use crate::replays::{ReplayError, Replays};
 
-const BG_COLOR: Color = Color::rgb(1.0, 0.93, 0.87);
-const TITLE_COLOR: Color = Color::rgb(1.0, 0.34, 0.2);
+pub const BG_COLOR: Color = Color::rgb(1.0, 0.93, 0.87);
+pub const TITLE_COLOR: Color = Color::rgb(1.0, 0.34, 0.2);
 pub const TEXT_COLOR: Color = Color::rgb(0.1, 0.1, 0.1);
 pub const NORMAL_BUTTON: Color = Color::rgb(0.97, 0.77, 0.06);
 pub const HOVERED_BUTTON: Color = Color::rgb(0.83, 0.67, 0.05);
 pub const HOVERED_PRESSED_BUTTON: Color = Color::rgb(0.7, 0.0, 0.2);
 pub const PRESSED_BUTTON: Color = Color::rgb(0.78, 0.0, 0.22);
-const FONT_SIZE: f32 = 42.0;
+pub const FONT_SIZE: f32 = 42.0;
+// Limits of rules settings
+const TARGET_SCORE_STEP: usize = 50;
+const MAX_HAND_SIZE: usize = 20;
+const MAX_RANK_COPIES: usize = 4;
+const MAX_DECKS: usize = 4;
+const CATCH_DELAY_STEP: usize = 250;
+const MAX_CATCH_DELAY: usize = 5000;
+const MIN_TURN_TIME: usize = 3;
+const MAX_TURN_TIME: usize = 30;
+const PENALTY_CAP_STEP: usize = 2;
+const MAX_PENALTY_CAP: usize = 40;
+const RULE_ROW_WIDTH: f32 = 340.0;
+const RULE_FONT_SIZE: f32 = 32.0;
+const CHOICE_BUTTON_WIDTH: f32 = 130.0;
+const BOT_LEVEL_BUTTON_WIDTH: f32 = 64.0;
+const SUMMARY_FONT_SIZE: f32 = 26.0;
+const SEED_BUTTON_WIDTH: f32 = 460.0;
+const REPLAY_BUTTON_WIDTH: f32 = 520.0;
+// Digits of the largest u64
+const MAX_SEED_DIGITS: usize = 20;
 
 // State used for the current menu screen
 #[derive(Clone, Copy, Default, Eq, PartialEq, Debug, Hash, States)]
@@ -16,6 +42,8 @@ enum MenuState {
     Main,
     SettingsDisplay,
     SettingsRules,
+    SettingsPresets,
+    Replays,
     #[default]
     Disabled,
 }
@@ -32,6 +60,26 @@ struct OnDisplaySettings;
 #[derive(Component)]
 struct OnRulesSettings;
 
+// Tag component used to tag entities added on the presets menu screen
+#[derive(Component)]
+struct OnPresetsSettings;
+
+// Tag component used to tag entities added on the replays menu screen
+#[derive(Component)]
+struct OnReplaysScreen;
+
+// Tag component of a text on the main menu telling why a game can't be started
+#[derive(Component)]
+struct MenuErrorText;
+
+// Text of the seed button on the main menu. Digits are typed in while `editing`
+#[derive(Component)]
+struct SeedField
+{
+    editing: bool,
+    digits: String,
+}
+
 // Tag component used to mark which setting is currently selected
 #[derive(Component)]
 struct SelectedOption;
@@ -42,32 +90,108 @@ enum MenuButtonAction {
     Play,
     SettingsDisplay,
     SettingsRules,
+    SettingsPresets,
+    Replays,
+    EditSeed,
     BackToMainMenu,
 }
 
+#[derive(Component, PartialEq, Eq)]
+enum PresetsButtonAction
+{
+    // Index of a preset in RulePresets
+    Choose(usize),
+    SaveCustom,
+}
+
+// Index of a replay in Replays
+#[derive(Component)]
+struct WatchReplay(usize);
+
 #[derive(Component, PartialEq, Eq)]
 enum RulesButtonAction
 {
     DecreasePlayers,
     IncreasePlayers,
+    DecreaseTarget,
+    IncreaseTarget,
+    DecreaseHandSize,
+    IncreaseHandSize,
+    DecreaseRankCopies,
+    IncreaseRankCopies,
+    DecreaseDecks,
+    IncreaseDecks,
+    DecreaseCatchDelay,
+    IncreaseCatchDelay,
+    DecreaseTurnTime,
+    IncreaseTurnTime,
+    DecreasePenaltyCap,
+    IncreasePenaltyCap,
     ToggleStackable,
+    ToggleStackDraw4,
     ToggleTurbo,
     ToggleClockwise,
     ToggleNoSkip,
+    ToggleChallenge,
+    ToggleActionCards,
+    ToggleWildCards,
+    ToggleSevenO,
+    ToggleJumpIn,
+    ToggleDrawUntilPlayable,
+    CycleAfterDraw,
+    // Seat of a bot
+    CycleBotLevel(usize),
+    ToggleDos,
+    ToggleLastCall,
+    ToggleProgressive,
+    ToggleDeflect,
 }
 
-#[derive(Component)]
-struct PlayersNumberText;
+// Number of a rule shown between its "-" and "+" buttons
+#[derive(Component, PartialEq, Eq, Clone, Copy)]
+enum RuleNumberText
+{
+    Players,
+    Target,
+    HandSize,
+    RankCopies,
+    Decks,
+    CatchDelay,
+    TurnTime,
+    PenaltyCap,
+}
 
-#[derive(Component, PartialEq, Eq)]
+// Rule with more than two options, named on a button that goes through them
+#[derive(Component, PartialEq, Eq, Clone, Copy)]
+enum RuleChoiceText
+{
+    AfterDraw,
+    BotLevel(usize),
+}
+
+#[derive(Component, PartialEq, Eq, Clone, Copy)]
 enum RuleButtonXMark
 {
     Stackable,
-    Turbo,
+    StackDraw4,
     Clockwise,
     NoSkip,
+    Challenge,
+    ActionCards,
+    WildCards,
+    SevenO,
+    JumpIn,
+    DrawUntilPlayable,
+    Dos,
+    LastCall,
+    Progressive,
+    Deflect,
 }
 
+// Why a game had to leave for the menu, shown on the main menu until it is opened again
+#[derive(Resource, Default)]
+pub struct MenuMessage(pub String);
+
 pub struct MenuPlugin;
 
 impl Plugin for MenuPlugin {
@@ -77,10 +201,13 @@ impl Plugin for MenuPlugin {
             // entering the `GameState::Menu` state.
             // Current screen in the menu is handled by an independent state from `GameState`
             .add_state::<MenuState>()
+            .init_resource::<MenuMessage>()
+            .add_startup_system(load_presets)
             .add_system(menu_setup.in_schedule(OnEnter(GameState::Menu)))
             // Systems to handle the main menu screen
             .add_systems((
                 main_menu_setup.in_schedule(OnEnter(MenuState::Main)),
+                seed_input.in_set(OnUpdate(MenuState::Main)),
                 despawn_screen::<OnMainMenuScreen>.in_schedule(OnExit(MenuState::Main)),
             ))
             // Systems to handle the display settings screen
@@ -93,8 +220,22 @@ impl Plugin for MenuPlugin {
             .add_systems((
                 rules_settings_menu_setup.in_schedule(OnEnter(MenuState::SettingsRules)),
                 rules_button_action.in_set(OnUpdate(MenuState::SettingsRules)),
+                rule_texts.run_if(resource_changed::<Rules>()).in_set(OnUpdate(MenuState::SettingsRules)),
+                show_bot_levels.run_if(resource_changed::<Rules>()).in_set(OnUpdate(MenuState::SettingsRules)),
                 despawn_screen::<OnRulesSettings>.in_schedule(OnExit(MenuState::SettingsRules)),
             ))
+            // Systems to handle the presets screen
+            .add_systems((
+                presets_menu_setup.in_schedule(OnEnter(MenuState::SettingsPresets)),
+                presets_button_action.in_set(OnUpdate(MenuState::SettingsPresets)),
+                despawn_screen::<OnPresetsSettings>.in_schedule(OnExit(MenuState::SettingsPresets)),
+            ))
+            // Systems to handle the replays screen
+            .add_systems((
+                replays_menu_setup.in_schedule(OnEnter(MenuState::Replays)),
+                watch_replay.in_set(OnUpdate(MenuState::Replays)),
+                despawn_screen::<OnReplaysScreen>.in_schedule(OnExit(MenuState::Replays)),
+            ))
             // Common systems to all screens that handles buttons behaviour
             .add_systems((menu_action, test, button_system).in_set(OnUpdate(GameState::Menu)));
     }
@@ -105,6 +246,32 @@ fn menu_setup(mut menu_state: ResMut<NextState<MenuState>>) {
     menu_state.set(MenuState::Main);
 }
 
+// A missing presets file is written out with the built-in presets, so that there is one to edit
+fn load_presets(mut commands: Commands)
+{
+    let mut presets = match RulePresets::load()
+    {
+        Ok(presets) => presets,
+        Err(PresetError::Io(err)) if err.kind() == std::io::ErrorKind::NotFound => {
+            let presets = RulePresets::default();
+            if let Err(err) = presets.save()
+            {
+                warn!("{}", err);
+            }
+            presets
+        },
+        Err(err) => {
+            warn!("{}, built-in presets are used instead", err);
+            RulePresets::default()
+        },
+    };
+    for (name, err) in presets.reset_invalid()
+    {
+        warn!("Preset {} can't be played, defaults are used instead: {}", name, err);
+    }
+    commands.insert_resource(presets);
+}
+
 // This system handles changing all buttons color based on mouse interaction
 fn button_system(
     mut interaction_query: Query<
@@ -145,61 +312,81 @@ fn setting_button<T: Resource + Component + PartialEq + Copy>(
 
 fn rules_button_action(
     interaction_q: Query<(&Interaction, &RulesButtonAction, Entity), (Changed<Interaction>, With<Button>)>,
-    mut number_text_q: Query<&mut Text, With<PlayersNumberText>>,
-    mut x_text_q: Query<(&mut Text, &RuleButtonXMark), Without<PlayersNumberText>>,
     mut rules: ResMut<Rules>,
     mut commands: Commands,
 ) {
     // TODO send events to a unified system for rules to accomodate handling menu with buttons
     for (interaction, button_action, entity) in &interaction_q
     {
-        let mut text_number = number_text_q.single_mut();
         if *interaction == Interaction::Clicked
         {
+            // Texts follow the rules in rule_texts
             match button_action {
-                RulesButtonAction::DecreasePlayers => {
-                    if rules.num_players > 1 { rules.num_players -= 1; } else { rules.num_players = 1 }
-                    text_number.sections[0].value = format!("{}", rules.num_players)
+                RulesButtonAction::DecreasePlayers => rules.num_players = rules.num_players.saturating_sub(1).max(1),
+                RulesButtonAction::IncreasePlayers => rules.num_players = (rules.num_players + 1).min(MAX_PLAYERS),
+                RulesButtonAction::DecreaseTarget => {
+                    rules.target_score = rules.target_score.saturating_sub(TARGET_SCORE_STEP).max(TARGET_SCORE_STEP);
                 },
-                RulesButtonAction::IncreasePlayers => {
-                    rules.num_players += 1;
-                    text_number.sections[0].value = format!("{}", rules.num_players)
+                RulesButtonAction::IncreaseTarget => rules.target_score += TARGET_SCORE_STEP,
+                RulesButtonAction::DecreaseHandSize => rules.hand_size = rules.hand_size.saturating_sub(1).max(1),
+                RulesButtonAction::IncreaseHandSize => rules.hand_size = (rules.hand_size + 1).min(MAX_HAND_SIZE),
+                RulesButtonAction::DecreaseRankCopies => rules.rank_copies = rules.rank_copies.saturating_sub(1).max(1),
+                RulesButtonAction::IncreaseRankCopies => rules.rank_copies = (rules.rank_copies + 1).min(MAX_RANK_COPIES),
+                RulesButtonAction::DecreaseDecks => rules.deck_multiplier = rules.deck_multiplier.saturating_sub(1).max(1),
+                RulesButtonAction::IncreaseDecks => rules.deck_multiplier = (rules.deck_multiplier + 1).min(MAX_DECKS),
+                RulesButtonAction::DecreaseCatchDelay => {
+                    rules.catch_delay = rules.catch_delay.saturating_sub(CATCH_DELAY_STEP).max(CATCH_DELAY_STEP);
                 },
-                RulesButtonAction::ToggleStackable => {
-                    rules.stackable_cards = !rules.stackable_cards;
-                    let x = if rules.stackable_cards { "x" } else { "" };
-                    'inner: for (mut text, marker) in x_text_q.iter_mut()
+                RulesButtonAction::IncreaseCatchDelay => rules.catch_delay = (rules.catch_delay + CATCH_DELAY_STEP).min(MAX_CATCH_DELAY),
+                RulesButtonAction::DecreaseTurnTime => rules.turn_time = rules.turn_time.saturating_sub(1).max(MIN_TURN_TIME),
+                RulesButtonAction::IncreaseTurnTime => rules.turn_time = (rules.turn_time + 1).min(MAX_TURN_TIME),
+                // Below the smallest cap penalties aren't capped at all
+                RulesButtonAction::DecreasePenaltyCap => {
+                    rules.penalty_cap = match rules.penalty_cap
                     {
-                        if *marker != RuleButtonXMark::Stackable { continue 'inner; }
-                        text.sections[0].value = format!("{}", x);
-                        break 'inner;
-                    }
-                }
+                        cap if cap <= MIN_PENALTY_CAP => 0,
+                        cap => cap - PENALTY_CAP_STEP,
+                    };
+                },
+                RulesButtonAction::IncreasePenaltyCap => {
+                    rules.penalty_cap = (rules.penalty_cap + PENALTY_CAP_STEP).clamp(MIN_PENALTY_CAP, MAX_PENALTY_CAP);
+                },
+                RulesButtonAction::ToggleStackable => rules.stackable_cards = !rules.stackable_cards,
+                RulesButtonAction::ToggleStackDraw4 => rules.stack_draw4 = !rules.stack_draw4,
                 RulesButtonAction::ToggleTurbo => {
                     rules.turbo = !rules.turbo;
                     if rules.turbo { commands.entity(entity).insert(SelectedOption); }
                     else { commands.entity(entity).remove::<SelectedOption>(); }
                 }
-                RulesButtonAction::ToggleClockwise => {
-                    rules.clockwise = !rules.clockwise;
-                    let x = if rules.clockwise { "x" } else { "" };
-                    'inner: for (mut text, marker) in x_text_q.iter_mut()
+                RulesButtonAction::ToggleClockwise => rules.clockwise = !rules.clockwise,
+                RulesButtonAction::ToggleNoSkip => rules.no_skip = !rules.no_skip,
+                RulesButtonAction::ToggleChallenge => rules.wild_draw4_challenge = !rules.wild_draw4_challenge,
+                RulesButtonAction::ToggleActionCards => rules.action_cards = !rules.action_cards,
+                RulesButtonAction::ToggleWildCards => rules.wild_cards = !rules.wild_cards,
+                RulesButtonAction::ToggleSevenO => rules.seven_o = !rules.seven_o,
+                RulesButtonAction::ToggleJumpIn => rules.jump_in = !rules.jump_in,
+                RulesButtonAction::ToggleDrawUntilPlayable => rules.draw_until_playable = !rules.draw_until_playable,
+                RulesButtonAction::CycleBotLevel(seat) => {
+                    rules.bot_levels[*seat] = match rules.bot_levels[*seat]
                     {
-                        if *marker != RuleButtonXMark::Clockwise { continue 'inner; }
-                        text.sections[0].value = format!("{}", x);
-                        break 'inner;
-                    }
-                }
-                RulesButtonAction::ToggleNoSkip => {
-                    rules.no_skip = !rules.no_skip;
-                    let x = if rules.no_skip { "x" } else { "" };
-                    'inner: for (mut text, marker) in x_text_q.iter_mut()
+                        BotLevel::Easy => BotLevel::Normal,
+                        BotLevel::Normal => BotLevel::Hard,
+                        BotLevel::Hard => BotLevel::Master,
+                        BotLevel::Master => BotLevel::Easy,
+                    };
+                },
+                RulesButtonAction::CycleAfterDraw => {
+                    rules.after_draw = match rules.after_draw
                     {
-                        if *marker != RuleButtonXMark::NoSkip { continue 'inner; }
-                        text.sections[0].value = format!("{}", x);
-                        break 'inner;
-                    }
-                }
+                        AfterDraw::AnyCard => AfterDraw::OnlyDrawn,
+                        AfterDraw::OnlyDrawn => AfterDraw::MustPlayDrawn,
+                        AfterDraw::MustPlayDrawn => AfterDraw::AnyCard,
+                    };
+                },
+                RulesButtonAction::ToggleDos => rules.dos = !rules.dos,
+                RulesButtonAction::ToggleLastCall => rules.last_call = !rules.last_call,
+                RulesButtonAction::ToggleProgressive => rules.progressive = !rules.progressive,
+                RulesButtonAction::ToggleDeflect => rules.deflect_penalty = !rules.deflect_penalty,
             }
         }
 
@@ -210,8 +397,52 @@ fn rules_button_action(
     }
 }
 
-fn main_menu_setup(mut commands: Commands, asset_server: Res<AssetServer>)
-{
+// Keeps choice texts apart from the other rule texts, which are queried mutably too
+type NotNumberOrXMark = (Without<RuleNumberText>, Without<RuleButtonXMark>);
+
+// Show current rules next to the buttons that change them
+fn rule_texts(
+    rules: Res<Rules>,
+    mut number_text_q: Query<(&mut Text, &RuleNumberText)>,
+    mut x_text_q: Query<(&mut Text, &RuleButtonXMark), Without<RuleNumberText>>,
+    mut choice_text_q: Query<(&mut Text, &RuleChoiceText), NotNumberOrXMark>,
+) {
+    for (mut text, number) in number_text_q.iter_mut()
+    {
+        text.sections[0].value = number.text(&rules);
+    }
+    for (mut text, marker) in x_text_q.iter_mut()
+    {
+        text.sections[0].value = marker.text(&rules).to_string();
+    }
+    for (mut text, choice) in choice_text_q.iter_mut()
+    {
+        text.sections[0].value = choice.text(&rules);
+    }
+}
+
+// Only seats at a table have a bot level to pick
+fn show_bot_levels(
+    rules: Res<Rules>,
+    mut buttons_q: Query<(&mut Style, &RulesButtonAction)>,
+) {
+    for (mut style, action) in buttons_q.iter_mut()
+    {
+        if let RulesButtonAction::CycleBotLevel(seat) = action
+        {
+            style.display = if *seat < rules.num_players { Display::Flex } else { Display::None };
+        }
+    }
+}
+
+fn main_menu_setup(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    rules: Res<Rules>,
+    presets: Res<RulePresets>,
+    seed: Res<GameSeed>,
+    mut message: ResMut<MenuMessage>,
+) {
     let font = asset_server.load("fonts/Vividly.otf");
     // Common style for all buttons on the screen
     let button_style = Style {
@@ -267,6 +498,27 @@ fn main_menu_setup(mut commands: Commands, asset_server: Res<AssetServer>)
                             ..default()
                         }),
                     );
+                    // Empty until rules don't allow to start a game
+                    parent.spawn((
+                        TextBundle::from_section(
+                            std::mem::take(&mut message.0),
+                            TextStyle {
+                                font: asset_server.load("fonts/FiraSans-Bold.ttf"),
+                                font_size: 30.0,
+                                color: PRESSED_BUTTON,
+                            },
+                        ),
+                        MenuErrorText,
+                    ));
+                    // Rules a game will be played with
+                    parent.spawn(TextBundle::from_section(
+                        presets.summary(&rules),
+                        TextStyle {
+                            font: asset_server.load("fonts/FiraSans-Bold.ttf"),
+                            font_size: SUMMARY_FONT_SIZE,
+                            color: TEXT_COLOR,
+                        },
+                    ));
                     // Play
                     parent
                         .spawn((
@@ -283,36 +535,59 @@ fn main_menu_setup(mut commands: Commands, asset_server: Res<AssetServer>)
                                 button_text_style.clone(),
                             ));
                         });
-                    // Display
-                    parent
-                        .spawn((
-                            ButtonBundle {
-                                style: button_style.clone(),
-                                background_color: NORMAL_BUTTON.into(),
-                                ..default()
-                            },
-                            MenuButtonAction::SettingsDisplay,
-                        ))
-                        .with_children(|parent| {
-                            parent.spawn(TextBundle::from_section(
-                                "Visuals",
-                                button_text_style.clone(),
-                            ));
-                        });
-                    // Rules
+                    // Visuals and replays, then rules and presets of them, side by side
+                    for row in [
+                        [("Visuals", MenuButtonAction::SettingsDisplay), ("Replays", MenuButtonAction::Replays)],
+                        [("Rules", MenuButtonAction::SettingsRules), ("Presets", MenuButtonAction::SettingsPresets)],
+                    ] {
+                        parent
+                            .spawn(NodeBundle::default())
+                            .with_children(|parent| {
+                                for (label, action) in row
+                                {
+                                    parent
+                                        .spawn((
+                                            ButtonBundle {
+                                                style: button_style.clone(),
+                                                background_color: NORMAL_BUTTON.into(),
+                                                ..default()
+                                            },
+                                            action,
+                                        ))
+                                        .with_children(|parent| {
+                                            parent.spawn(TextBundle::from_section(label, button_text_style.clone()));
+                                        });
+                                }
+                            });
+                    }
+                    // Seed of the next match, typed in after a click
+                    let seed_field = SeedField {
+                        editing: false,
+                        digits: seed.fixed.map(|seed| seed.to_string()).unwrap_or_default(),
+                    };
                     parent
                         .spawn((
                             ButtonBundle {
-                                style: button_style.clone(),
+                                style: Style {
+                                    size: Size::new(Val::Px(SEED_BUTTON_WIDTH), Val::Px(50.0)),
+                                    ..button_style.clone()
+                                },
                                 background_color: NORMAL_BUTTON.into(),
                                 ..default()
                             },
-                            MenuButtonAction::SettingsRules,
+                            MenuButtonAction::EditSeed,
                         ))
                         .with_children(|parent| {
-                            parent.spawn(TextBundle::from_section(
-                                "Rules",
-                                button_text_style.clone(),
+                            parent.spawn((
+                                TextBundle::from_section(
+                                    seed_field.label(),
+                                    TextStyle {
+                                        font: font.clone(),
+                                        font_size: RULE_FONT_SIZE,
+                                        color: TEXT_COLOR,
+                                    },
+                                ),
+                                seed_field,
                             ));
                         });
                 });
@@ -438,6 +713,20 @@ fn rules_settings_menu_setup(
         font_size: FONT_SIZE,
         color: TEXT_COLOR,
     };
+    // Three columns of rules have to fit a window
+    let rule_text_style = TextStyle {
+        font_size: RULE_FONT_SIZE,
+        ..button_text_style.clone()
+    };
+    let sign_text_style = TextStyle {
+        font: asset_server.load("fonts/FiraSans-Bold.ttf"),
+        ..rule_text_style.clone()
+    };
+    let column_style = Style {
+        flex_direction: FlexDirection::Column,
+        margin: UiRect::horizontal(Val::Px(20.0)),
+        ..default()
+    };
 
     commands
         .spawn((
@@ -464,204 +753,622 @@ fn rules_settings_menu_setup(
                     ..default()
                 })
                 .with_children(|parent| {
-                    // Number of Players
                     parent
                         .spawn(NodeBundle {
                             style: Style {
-                                align_items: AlignItems::Center,
+                                margin: UiRect::top(Val::Px(20.0)),
                                 ..default()
                             },
-                            background_color: BG_COLOR.into(),
                             ..default()
                         })
                         .with_children(|parent| {
-                            parent.spawn(TextBundle::from_section(
-                                "Players: ",
-                                button_text_style.clone(),
-                            ));
-                            parent.spawn(ButtonBundle {
-                                style: Style {
-                                    size: Size::new(Val::Px(40.0), Val::Px(40.0)),
-                                    ..button_style.clone()
-                                },
-                                background_color: NORMAL_BUTTON.into(),
-                                ..default()
-                            }).insert(RulesButtonAction::DecreasePlayers).with_children(|parent| {
-                                parent.spawn(TextBundle::from_section(
-                                    "-",
-                                    TextStyle {
-                                        font: asset_server.load("fonts/FiraSans-Bold.ttf"),
-                                        ..button_text_style.clone()
+                            // Table and deck
+                            parent
+                                .spawn(NodeBundle { style: column_style.clone(), ..default() })
+                                .with_children(|parent| {
+                                    let numbers = [
+                                        ("Players", RulesButtonAction::DecreasePlayers, RulesButtonAction::IncreasePlayers, RuleNumberText::Players),
+                                        ("Target score", RulesButtonAction::DecreaseTarget, RulesButtonAction::IncreaseTarget, RuleNumberText::Target),
+                                        ("Hand size", RulesButtonAction::DecreaseHandSize, RulesButtonAction::IncreaseHandSize, RuleNumberText::HandSize),
+                                        ("Rank copies", RulesButtonAction::DecreaseRankCopies, RulesButtonAction::IncreaseRankCopies, RuleNumberText::RankCopies),
+                                        ("Decks", RulesButtonAction::DecreaseDecks, RulesButtonAction::IncreaseDecks, RuleNumberText::Decks),
+                                        ("Penalty cap", RulesButtonAction::DecreasePenaltyCap, RulesButtonAction::IncreasePenaltyCap, RuleNumberText::PenaltyCap),
+                                    ];
+                                    for (label, decrease, increase, marker) in numbers
+                                    {
+                                        number_row(parent, label, (decrease, increase), marker, &rules, &rule_text_style, &sign_text_style);
                                     }
-                                ));
-                            });
-                            parent.spawn((TextBundle::from_section(
-                                format!("{}", rules.num_players),
-                                button_text_style.clone(),
-                            ), PlayersNumberText));
-                            parent.spawn(ButtonBundle {
-                                style: Style {
-                                    size: Size::new(Val::Px(40.0), Val::Px(40.0)),
-                                    ..button_style.clone()
-                                },
-                                background_color: NORMAL_BUTTON.into(),
-                                ..default()
-                            }).insert(RulesButtonAction::IncreasePlayers).with_children(|parent| {
-                                parent.spawn(TextBundle::from_section(
-                                    "+",
-                                    TextStyle {
-                                        font: asset_server.load("fonts/FiraSans-Bold.ttf"),
-                                        ..button_text_style.clone()
+                                    toggle_row(parent, "Action cards", RulesButtonAction::ToggleActionCards, RuleButtonXMark::ActionCards, &rules, &rule_text_style);
+                                    toggle_row(parent, "Wild cards", RulesButtonAction::ToggleWildCards, RuleButtonXMark::WildCards, &rules, &rule_text_style);
+                                });
+                            // Drawing and draw cards
+                            parent
+                                .spawn(NodeBundle { style: column_style.clone(), ..default() })
+                                .with_children(|parent| {
+                                    let toggles = [
+                                        ("No skip", RulesButtonAction::ToggleNoSkip, RuleButtonXMark::NoSkip),
+                                        ("Draw to match", RulesButtonAction::ToggleDrawUntilPlayable, RuleButtonXMark::DrawUntilPlayable),
+                                        ("Stackable cards", RulesButtonAction::ToggleStackable, RuleButtonXMark::Stackable),
+                                        ("Stack +4 on +2", RulesButtonAction::ToggleStackDraw4, RuleButtonXMark::StackDraw4),
+                                        ("Progressive +2/+4", RulesButtonAction::ToggleProgressive, RuleButtonXMark::Progressive),
+                                        ("Skip deflects", RulesButtonAction::ToggleDeflect, RuleButtonXMark::Deflect),
+                                        ("Challenge +4", RulesButtonAction::ToggleChallenge, RuleButtonXMark::Challenge),
+                                    ];
+                                    for (label, action, marker) in toggles
+                                    {
+                                        toggle_row(parent, label, action, marker, &rules, &rule_text_style);
                                     }
-                                ));
-                            });
+                                });
+                            // How a turn goes
+                            parent
+                                .spawn(NodeBundle { style: column_style, ..default() })
+                                .with_children(|parent| {
+                                    let toggles = [
+                                        ("DOS mode", RulesButtonAction::ToggleDos, RuleButtonXMark::Dos),
+                                        ("Clockwise", RulesButtonAction::ToggleClockwise, RuleButtonXMark::Clockwise),
+                                        ("Seven-O", RulesButtonAction::ToggleSevenO, RuleButtonXMark::SevenO),
+                                        ("Jump-in", RulesButtonAction::ToggleJumpIn, RuleButtonXMark::JumpIn),
+                                        ("Last card call", RulesButtonAction::ToggleLastCall, RuleButtonXMark::LastCall),
+                                    ];
+                                    for (label, action, marker) in toggles
+                                    {
+                                        toggle_row(parent, label, action, marker, &rules, &rule_text_style);
+                                    }
+                                    choice_row(parent, "After draw", RulesButtonAction::CycleAfterDraw, RuleChoiceText::AfterDraw, &rules, &rule_text_style);
+                                    // Turbo mode
+                                    parent
+                                        .spawn(NodeBundle { style: rule_row_style(), ..default() })
+                                        .with_children(|parent| {
+                                            parent.spawn(TextBundle::from_section(
+                                                "Turbo",
+                                                rule_text_style.clone(),
+                                            ));
+                                            let mut entity = parent.spawn(ButtonBundle {
+                                                style: rule_button_style(),
+                                                background_color: NORMAL_BUTTON.into(),
+                                                ..default()
+                                            });
+                                            entity.insert(RulesButtonAction::ToggleTurbo);
+                                            if rules.turbo {entity.insert(SelectedOption);}
+                                        });
+                                    number_row(
+                                        parent,
+                                        "Turn (s)",
+                                        (RulesButtonAction::DecreaseTurnTime, RulesButtonAction::IncreaseTurnTime),
+                                        RuleNumberText::TurnTime,
+                                        &rules,
+                                        &rule_text_style,
+                                        &sign_text_style,
+                                    );
+                                    number_row(
+                                        parent,
+                                        "Catch (ms)",
+                                        (RulesButtonAction::DecreaseCatchDelay, RulesButtonAction::IncreaseCatchDelay),
+                                        RuleNumberText::CatchDelay,
+                                        &rules,
+                                        &rule_text_style,
+                                        &sign_text_style,
+                                    );
+                                });
                         });
-                    // Stackable cards
+                    // Level of every bot, seats without a player are hidden by show_bot_levels
                     parent
                         .spawn(NodeBundle {
                             style: Style {
                                 align_items: AlignItems::Center,
                                 ..default()
                             },
-                            background_color: BG_COLOR.into(),
                             ..default()
                         })
                         .with_children(|parent| {
-                            parent.spawn(TextBundle::from_section(
-                                "Stackable cards",
-                                button_text_style.clone(),
-                            ));
-                            parent
-                                .spawn((ButtonBundle {
-                                    style: Style {
-                                        size: Size::new(Val::Px(40.0), Val::Px(40.0)),
-                                        ..button_style.clone()
-                                    },
-                                    background_color: NORMAL_BUTTON.into(),
-                                    ..default()
-                                },
-                                RulesButtonAction::ToggleStackable,
-                            )).with_children(|parent| {
-                                let x = if rules.stackable_cards { "x" } else { "" };
-                                parent.spawn((TextBundle::from_section(
-                                    format!("{}", x),
-                                    button_text_style.clone(),
-                                ), RuleButtonXMark::Stackable));
-                            });
+                            parent.spawn(TextBundle::from_section("Bots (E/N/H/M)", rule_text_style.clone()));
+                            for seat in 1..MAX_PLAYERS
+                            {
+                                let choice = RuleChoiceText::BotLevel(seat);
+                                parent
+                                    .spawn((
+                                        ButtonBundle {
+                                            style: Style {
+                                                size: Size::new(Val::Px(BOT_LEVEL_BUTTON_WIDTH), Val::Px(40.0)),
+                                                display: if seat < rules.num_players { Display::Flex } else { Display::None },
+                                                ..rule_button_style()
+                                            },
+                                            background_color: NORMAL_BUTTON.into(),
+                                            ..default()
+                                        },
+                                        RulesButtonAction::CycleBotLevel(seat),
+                                    ))
+                                    .with_children(|parent| {
+                                        parent.spawn((TextBundle::from_section(choice.text(&rules), rule_text_style.clone()), choice));
+                                    });
+                            }
                         });
-                    // No Skip
+                    // Back to menu
                     parent
-                        .spawn(NodeBundle {
-                            style: Style {
-                                align_items: AlignItems::Center,
+                        .spawn((
+                            ButtonBundle {
+                                style: button_style,
+                                background_color: NORMAL_BUTTON.into(),
                                 ..default()
                             },
-                            background_color: BG_COLOR.into(),
-                            ..default()
-                        })
+                            MenuButtonAction::BackToMainMenu,
+                        ))
                         .with_children(|parent| {
-                            parent.spawn(TextBundle::from_section(
-                                "No skip",
-                                button_text_style.clone(),
-                            ));
-                            parent
-                                .spawn((ButtonBundle {
-                                    style: Style {
-                                        size: Size::new(Val::Px(40.0), Val::Px(40.0)),
-                                        ..button_style.clone()
-                                    },
-                                    background_color: NORMAL_BUTTON.into(),
-                                    ..default()
-                                },
-                                RulesButtonAction::ToggleNoSkip,
-                            )).with_children(|parent| {
-                                let x = if rules.no_skip { "x" } else { "" };
-                                parent.spawn((TextBundle::from_section(
-                                    format!("{}", x),
-                                    button_text_style.clone(),
-                                ), RuleButtonXMark::NoSkip));
-                            });
+                            parent.spawn(TextBundle::from_section("Back", button_text_style));
                         });
-                    // Clockwise
-                    parent
-                        .spawn(NodeBundle {
-                            style: Style {
-                                align_items: AlignItems::Center,
+                });
+        });
+}
+
+// One button per preset, the one matching current rules is selected
+fn presets_menu_setup(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    rules: Res<Rules>,
+    presets: Res<RulePresets>,
+) {
+    let button_style = Style {
+        size: Size::new(Val::Px(300.0), Val::Px(65.0)),
+        margin: UiRect::all(Val::Px(10.0)),
+        justify_content: JustifyContent::Center,
+        align_items: AlignItems::Center,
+        ..default()
+    };
+    let button_text_style = TextStyle {
+        font: asset_server.load("fonts/Vividly.otf"),
+        font_size: FONT_SIZE,
+        color: TEXT_COLOR,
+    };
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    size: Size::new(Val::Percent(100.0), Val::Percent(100.0)),
+                    align_items: AlignItems::Center,
+                    justify_content: JustifyContent::Center,
+                    ..default()
+                },
+                ..default()
+            },
+            OnPresetsSettings,
+        ))
+        .with_children(|parent| {
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        flex_direction: FlexDirection::Column,
+                        align_items: AlignItems::Center,
+                        padding: UiRect::all(Val::Px(20.0)),
+                        ..default()
+                    },
+                    background_color: BG_COLOR.into(),
+                    ..default()
+                })
+                .with_children(|parent| {
+                    for (index, preset) in presets.presets().iter().enumerate()
+                    {
+                        let mut entity = parent.spawn((
+                            ButtonBundle {
+                                style: button_style.clone(),
+                                background_color: NORMAL_BUTTON.into(),
                                 ..default()
                             },
-                            background_color: BG_COLOR.into(),
-                            ..default()
-                        })
+                            PresetsButtonAction::Choose(index),
+                        ));
+                        entity.with_children(|parent| {
+                            parent.spawn(TextBundle::from_section(preset.name.clone(), button_text_style.clone()));
+                        });
+                        if preset.rules == *rules
+                        {
+                            entity.insert((SelectedOption, BackgroundColor(PRESSED_BUTTON)));
+                        }
+                    }
+                    parent
+                        .spawn(NodeBundle::default())
                         .with_children(|parent| {
-                            parent.spawn(TextBundle::from_section(
-                                "Clockwise",
-                                button_text_style.clone(),
-                            ));
                             parent
-                                .spawn((ButtonBundle {
-                                    style: Style {
-                                        size: Size::new(Val::Px(40.0), Val::Px(40.0)),
-                                        ..button_style.clone()
+                                .spawn((
+                                    ButtonBundle {
+                                        style: button_style.clone(),
+                                        background_color: NORMAL_BUTTON.into(),
+                                        ..default()
                                     },
+                                    PresetsButtonAction::SaveCustom,
+                                ))
+                                .with_children(|parent| {
+                                    parent.spawn(TextBundle::from_section("Save as custom", button_text_style.clone()));
+                                });
+                            parent
+                                .spawn((
+                                    ButtonBundle {
+                                        style: button_style,
+                                        background_color: NORMAL_BUTTON.into(),
+                                        ..default()
+                                    },
+                                    MenuButtonAction::BackToMainMenu,
+                                ))
+                                .with_children(|parent| {
+                                    parent.spawn(TextBundle::from_section("Back", button_text_style));
+                                });
+                        });
+                });
+        });
+}
+
+fn presets_button_action(
+    interaction_q: Query<(&Interaction, &PresetsButtonAction), ButtonInteraction>,
+    mut menu_state: ResMut<NextState<MenuState>>,
+    mut rules: ResMut<Rules>,
+    mut presets: ResMut<RulePresets>,
+) {
+    for (interaction, button_action) in &interaction_q
+    {
+        if *interaction == Interaction::Clicked
+        {
+            match button_action
+            {
+                PresetsButtonAction::Choose(index) => *rules = presets.presets()[*index].rules,
+                PresetsButtonAction::SaveCustom => {
+                    presets.save_custom(*rules);
+                    if let Err(err) = presets.save()
+                    {
+                        warn!("{}", err);
+                    }
+                },
+            }
+            // Screen is built again to select a preset and to show a new custom one
+            menu_state.set(MenuState::SettingsPresets);
+        }
+    }
+}
+
+// Latest rounds first, each one a button that plays it back
+fn replays_menu_setup(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+) {
+    let replays = match Replays::load()
+    {
+        Ok(replays) => replays,
+        Err(ReplayError::Io(err)) if err.kind() == std::io::ErrorKind::NotFound => Replays::default(),
+        Err(err) => {
+            warn!("{}", err);
+            Replays::default()
+        },
+    };
+    let button_style = Style {
+        size: Size::new(Val::Px(REPLAY_BUTTON_WIDTH), Val::Px(50.0)),
+        margin: UiRect::all(Val::Px(6.0)),
+        justify_content: JustifyContent::Center,
+        align_items: AlignItems::Center,
+        ..default()
+    };
+    let replay_text_style = TextStyle {
+        font: asset_server.load("fonts/FiraSans-Bold.ttf"),
+        font_size: SUMMARY_FONT_SIZE,
+        color: TEXT_COLOR,
+    };
+    let button_text_style = TextStyle {
+        font: asset_server.load("fonts/Vividly.otf"),
+        font_size: FONT_SIZE,
+        color: TEXT_COLOR,
+    };
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    size: Size::new(Val::Percent(100.0), Val::Percent(100.0)),
+                    align_items: AlignItems::Center,
+                    justify_content: JustifyContent::Center,
+                    ..default()
+                },
+                ..default()
+            },
+            OnReplaysScreen,
+        ))
+        .with_children(|parent| {
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        flex_direction: FlexDirection::Column,
+                        align_items: AlignItems::Center,
+                        padding: UiRect::all(Val::Px(20.0)),
+                        ..default()
+                    },
+                    background_color: BG_COLOR.into(),
+                    ..default()
+                })
+                .with_children(|parent| {
+                    // Empty until a replay can't be played back
+                    parent.spawn((
+                        TextBundle::from_section(
+                            if replays.replays().is_empty() { "Rounds you play show up here" } else { "" },
+                            replay_text_style.clone(),
+                        ),
+                        MenuErrorText,
+                    ));
+                    for (index, replay) in replays.replays().iter().enumerate().rev()
+                    {
+                        parent
+                            .spawn((
+                                ButtonBundle {
+                                    style: button_style.clone(),
                                     background_color: NORMAL_BUTTON.into(),
                                     ..default()
                                 },
-                                RulesButtonAction::ToggleClockwise,
-                            )).with_children(|parent| {
-                                let x = if rules.no_skip { "x" } else { "" };
-                                parent.spawn((TextBundle::from_section(
-                                    format!("{}", x),
-                                    button_text_style.clone(),
-                                ), RuleButtonXMark::Clockwise));
+                                WatchReplay(index),
+                            ))
+                            .with_children(|parent| {
+                                parent.spawn(TextBundle::from_section(replay.summary(), replay_text_style.clone()));
                             });
-                        });
-                    // Turbo mode
+                    }
                     parent
-                        .spawn(NodeBundle {
-                            style: Style {
-                                align_items: AlignItems::Center,
+                        .spawn((
+                            ButtonBundle {
+                                style: Style {
+                                    size: Size::new(Val::Px(220.0), Val::Px(65.0)),
+                                    margin: UiRect::all(Val::Px(20.0)),
+                                    ..button_style
+                                },
+                                background_color: NORMAL_BUTTON.into(),
                                 ..default()
                             },
-                            background_color: BG_COLOR.into(),
-                            ..default()
-                        })
+                            MenuButtonAction::BackToMainMenu,
+                        ))
                         .with_children(|parent| {
-                            parent.spawn(TextBundle::from_section(
-                                "Turbo",
-                                button_text_style.clone(),
-                            ));
-                            let mut entity = parent.spawn(ButtonBundle {
-                                style: Style {
-                                    size: Size::new(Val::Px(40.0), Val::Px(40.0)),
-                                    ..button_style.clone()
-                                },
+                            parent.spawn(TextBundle::from_section("Back", button_text_style));
+                        });
+                });
+        });
+    commands.insert_resource(replays);
+}
+
+// Replays recorded by another version of the game may not follow its rules, they are played
+// through before leaving the menu
+fn watch_replay(
+    mut commands: Commands,
+    interaction_q: Query<(&Interaction, &WatchReplay), ButtonInteraction>,
+    mut error_q: Query<&mut Text, With<MenuErrorText>>,
+    mut menu_state: ResMut<NextState<MenuState>>,
+    mut game_state: ResMut<NextState<GameState>>,
+    replays: Res<Replays>,
+) {
+    for (interaction, WatchReplay(index)) in &interaction_q
+    {
+        if *interaction != Interaction::Clicked { continue; }
+
+        let replay = &replays.replays()[*index];
+        match replay.table_after(replay.actions.len())
+        {
+            Ok(_) => {
+                commands.insert_resource(Playback::new(replay.clone()));
+                game_state.set(GameState::Replay);
+                menu_state.set(MenuState::Disabled);
+            },
+            Err(err) => error_q.single_mut().sections[0].value = err.to_string(),
+        }
+    }
+}
+
+// Row of a rules screen with a label on the left and buttons on the right
+fn rule_row_style() -> Style
+{
+    Style {
+        size: Size::new(Val::Px(RULE_ROW_WIDTH), Val::Auto),
+        align_items: AlignItems::Center,
+        justify_content: JustifyContent::SpaceBetween,
+        ..default()
+    }
+}
+
+fn rule_button_style() -> Style
+{
+    Style {
+        size: Size::new(Val::Px(40.0), Val::Px(40.0)),
+        margin: UiRect::all(Val::Px(8.0)),
+        justify_content: JustifyContent::Center,
+        align_items: AlignItems::Center,
+        ..default()
+    }
+}
+
+// Label with a number of a rule between "-" and "+" buttons
+fn number_row(
+    parent: &mut ChildBuilder,
+    label: &str,
+    (decrease, increase): (RulesButtonAction, RulesButtonAction),
+    marker: RuleNumberText,
+    rules: &Rules,
+    text_style: &TextStyle,
+    sign_style: &TextStyle,
+) {
+    parent
+        .spawn(NodeBundle { style: rule_row_style(), ..default() })
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section(label, text_style.clone()));
+            parent
+                .spawn(NodeBundle { style: Style { align_items: AlignItems::Center, ..default() }, ..default() })
+                .with_children(|parent| {
+                    parent
+                        .spawn((
+                            ButtonBundle {
+                                style: rule_button_style(),
                                 background_color: NORMAL_BUTTON.into(),
                                 ..default()
-                            });
-                            entity.insert(RulesButtonAction::ToggleTurbo);
-                            if rules.turbo {entity.insert(SelectedOption);}
+                            },
+                            decrease,
+                        ))
+                        .with_children(|parent| {
+                            parent.spawn(TextBundle::from_section("-", sign_style.clone()));
                         });
-                    // Back to menu
+                    parent.spawn((
+                        TextBundle::from_section(marker.text(rules), text_style.clone()),
+                        marker,
+                    ));
                     parent
                         .spawn((
                             ButtonBundle {
-                                style: button_style,
+                                style: rule_button_style(),
                                 background_color: NORMAL_BUTTON.into(),
                                 ..default()
                             },
-                            MenuButtonAction::BackToMainMenu,
+                            increase,
                         ))
                         .with_children(|parent| {
-                            parent.spawn(TextBundle::from_section("Back", button_text_style));
+                            parent.spawn(TextBundle::from_section("+", sign_style.clone()));
                         });
                 });
         });
 }
 
+// Label with a button marked by "x" while a rule is on
+fn toggle_row(
+    parent: &mut ChildBuilder,
+    label: &str,
+    action: RulesButtonAction,
+    marker: RuleButtonXMark,
+    rules: &Rules,
+    text_style: &TextStyle,
+) {
+    parent
+        .spawn(NodeBundle { style: rule_row_style(), ..default() })
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section(label, text_style.clone()));
+            parent
+                .spawn((
+                    ButtonBundle {
+                        style: rule_button_style(),
+                        background_color: NORMAL_BUTTON.into(),
+                        ..default()
+                    },
+                    action,
+                ))
+                .with_children(|parent| {
+                    parent.spawn((TextBundle::from_section(marker.text(rules), text_style.clone()), marker));
+                });
+        });
+}
+
+// Label with a button naming the option of a rule, a click picks the next one
+fn choice_row(
+    parent: &mut ChildBuilder,
+    label: &str,
+    action: RulesButtonAction,
+    choice: RuleChoiceText,
+    rules: &Rules,
+    text_style: &TextStyle,
+) {
+    parent
+        .spawn(NodeBundle { style: rule_row_style(), ..default() })
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section(label, text_style.clone()));
+            parent
+                .spawn((
+                    ButtonBundle {
+                        style: Style {
+                            size: Size::new(Val::Px(CHOICE_BUTTON_WIDTH), Val::Px(40.0)),
+                            ..rule_button_style()
+                        },
+                        background_color: NORMAL_BUTTON.into(),
+                        ..default()
+                    },
+                    action,
+                ))
+                .with_children(|parent| {
+                    parent.spawn((TextBundle::from_section(choice.text(rules), text_style.clone()), choice));
+                });
+        });
+}
+
+impl RuleNumberText
+{
+    fn value(&self, rules: &Rules) -> usize
+    {
+        match self
+        {
+            RuleNumberText::Players => rules.num_players,
+            RuleNumberText::Target => rules.target_score,
+            RuleNumberText::HandSize => rules.hand_size,
+            RuleNumberText::RankCopies => rules.rank_copies,
+            RuleNumberText::Decks => rules.deck_multiplier,
+            RuleNumberText::CatchDelay => rules.catch_delay,
+            RuleNumberText::TurnTime => rules.turn_time,
+            RuleNumberText::PenaltyCap => rules.penalty_cap,
+        }
+    }
+
+    // Zero cap stands for no cap
+    fn text(&self, rules: &Rules) -> String
+    {
+        match (self, self.value(rules))
+        {
+            (RuleNumberText::PenaltyCap, 0) => "-".to_string(),
+            (_, value) => value.to_string(),
+        }
+    }
+}
+
+impl RuleChoiceText
+{
+    fn text(&self, rules: &Rules) -> String
+    {
+        match self
+        {
+            RuleChoiceText::AfterDraw => match rules.after_draw
+            {
+                AfterDraw::AnyCard => "Any card",
+                AfterDraw::OnlyDrawn => "Drawn",
+                AfterDraw::MustPlayDrawn => "Must play",
+            }.to_string(),
+            // Seat number and the first letter of a level, for all of them to fit in a row
+            RuleChoiceText::BotLevel(seat) => {
+                let level = match rules.bot_levels[*seat]
+                {
+                    BotLevel::Easy => "E",
+                    BotLevel::Normal => "N",
+                    BotLevel::Hard => "H",
+                    BotLevel::Master => "M",
+                };
+                format!("{}{}", seat + 1, level)
+            },
+        }
+    }
+}
+
+impl RuleButtonXMark
+{
+    fn text(&self, rules: &Rules) -> &'static str
+    {
+        let on = match self
+        {
+            RuleButtonXMark::Stackable => rules.stackable_cards,
+            RuleButtonXMark::StackDraw4 => rules.stack_draw4,
+            RuleButtonXMark::Clockwise => rules.clockwise,
+            RuleButtonXMark::NoSkip => rules.no_skip,
+            RuleButtonXMark::Challenge => rules.wild_draw4_challenge,
+            RuleButtonXMark::ActionCards => rules.action_cards,
+            RuleButtonXMark::WildCards => rules.wild_cards,
+            RuleButtonXMark::SevenO => rules.seven_o,
+            RuleButtonXMark::JumpIn => rules.jump_in,
+            RuleButtonXMark::DrawUntilPlayable => rules.draw_until_playable,
+            RuleButtonXMark::Dos => rules.dos,
+            RuleButtonXMark::LastCall => rules.last_call,
+            RuleButtonXMark::Progressive => rules.progressive,
+            RuleButtonXMark::Deflect => rules.deflect_penalty,
+        };
+        if on { "x" } else { "" }
+    }
+}
+
 fn menu_action(
     interaction_query: Query<(&Interaction, &MenuButtonAction), (Changed<Interaction>, With<Button>)>,
+    mut error_q: Query<&mut Text, With<MenuErrorText>>,
     mut menu_state: ResMut<NextState<MenuState>>,
     mut game_state: ResMut<NextState<GameState>>,
+    mut seed_q: Query<&mut SeedField>,
+    rules: Res<Rules>,
 ) {
     for (interaction, menu_button_action) in &interaction_query
     {
@@ -669,18 +1376,70 @@ fn menu_action(
         {
             match menu_button_action
             {
-                MenuButtonAction::Play => {
-                    game_state.set(GameState::Game);
-                    menu_state.set(MenuState::Disabled);
+                MenuButtonAction::Play => match rules.validate()
+                {
+                    Ok(()) => {
+                        game_state.set(GameState::Game);
+                        menu_state.set(MenuState::Disabled);
+                    },
+                    Err(err) => error_q.single_mut().sections[0].value = err.to_string(),
                 },
                 MenuButtonAction::SettingsDisplay => menu_state.set(MenuState::SettingsDisplay),
                 MenuButtonAction::SettingsRules => menu_state.set(MenuState::SettingsRules),
+                MenuButtonAction::SettingsPresets => menu_state.set(MenuState::SettingsPresets),
+                MenuButtonAction::Replays => menu_state.set(MenuState::Replays),
+                MenuButtonAction::EditSeed => {
+                    for mut field in seed_q.iter_mut()
+                    {
+                        field.editing = !field.editing;
+                    }
+                },
                 MenuButtonAction::BackToMainMenu => menu_state.set(MenuState::Main),
             }
         }
     }
 }
 
+// Digits typed in go to the seed of the next match, Enter stops typing. A seed left empty
+// lets every match pick a random one
+fn seed_input(
+    mut characters: EventReader<ReceivedCharacter>,
+    key: Res<Input<KeyCode>>,
+    mut seed_q: Query<(&mut Text, &mut SeedField)>,
+    mut seed: ResMut<GameSeed>,
+) {
+    let typed: Vec<char> = characters.iter().map(|event| event.char).collect();
+    for (mut text, mut field) in seed_q.iter_mut()
+    {
+        if field.editing
+        {
+            for &digit in typed.iter().filter(|char| char.is_ascii_digit())
+            {
+                let mut digits = field.digits.clone();
+                digits.push(digit);
+                if digits.len() <= MAX_SEED_DIGITS && digits.parse::<u64>().is_ok()
+                {
+                    field.digits = digits;
+                }
+            }
+            if key.just_pressed(KeyCode::Back)
+            {
+                field.digits.pop();
+            }
+            if key.just_pressed(KeyCode::Return)
+            {
+                field.editing = false;
+            }
+            seed.fixed = field.digits.parse().ok();
+        }
+        // A click on the button starts and stops typing too
+        if field.is_changed()
+        {
+            text.sections[0].value = field.label();
+        }
+    }
+}
+
 fn test(
     rules: Res<Rules>,
     key: Res<Input<KeyCode>>,
@@ -690,3 +1449,16 @@ fn test(
         info!("Rules: {:?}", rules);
     }
 }
+
+impl SeedField
+{
+    fn label(&self) -> String
+    {
+        match (self.editing, self.digits.is_empty())
+        {
+            (true, _) => format!("Seed: {}_", self.digits),
+            (false, true) => "Seed: random".to_string(),
+            (false, false) => format!("Seed: {}", self.digits),
+        }
+    }
+}
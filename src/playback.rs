@@ -0,0 +1,337 @@
+use bevy::prelude::*;
+use crate::{ButtonInteraction, GameState};
+use crate::engine::GameEngine;
+use crate::game::{spawn_table, GameItem, TableEvent};
+use crate::game_ui::button_colors;
+use crate::menu::{MenuMessage, TEXT_COLOR, NORMAL_BUTTON};
+use crate::replays::{Replay, ReplayError};
+
+// Seconds between moves at normal speed
+const STEP_TIME: f32 = 1.0;
+const SPEEDS: [f32; 5] = [0.25, 0.5, 1.0, 2.0, 4.0];
+const NORMAL_SPEED: usize = 2;
+const CONTROL_FONT_SIZE: f32 = 32.0;
+
+// Replay picked in the menu, played back on a table while in GameState::Replay
+#[derive(Resource)]
+pub struct Playback
+{
+    replay: Replay,
+    // Actions applied to a table so far
+    step: usize,
+    paused: bool,
+    // Index into SPEEDS
+    speed: usize,
+    timer: Timer,
+}
+
+#[derive(Component)]
+enum PlaybackButtonAction {
+    StepBack,
+    Pause,
+    StepForward,
+    Slower,
+    Faster,
+    Menu,
+}
+
+// Tag component of a text telling which move of a replay is on a table
+#[derive(Component)]
+struct PlaybackText;
+
+// Tag component of a text of the pause button, which turns into a play button
+#[derive(Component)]
+struct PauseText;
+
+pub struct PlaybackPlugin;
+
+impl Plugin for PlaybackPlugin
+{
+    fn build(&self, app: &mut App)
+    {
+        // A replay that couldn't be dealt goes back to the menu without a table
+        app.configure_set(OnUpdate(GameState::Replay).run_if(resource_exists::<GameEngine>()))
+            .add_system(playback_setup.in_schedule(OnEnter(GameState::Replay)))
+            .add_systems((playback_action, keyboard_action, button_colors).in_set(OnUpdate(GameState::Replay)))
+            .add_system(play_back.after(playback_action).after(keyboard_action).in_set(OnUpdate(GameState::Replay)))
+            .add_system(show_playback.after(play_back).run_if(resource_changed::<Playback>()).in_set(OnUpdate(GameState::Replay)));
+    }
+}
+
+impl Playback
+{
+    pub fn new(replay: Replay) -> Self
+    {
+        Playback {
+            replay,
+            step: 0,
+            paused: false,
+            speed: NORMAL_SPEED,
+            timer: Timer::from_seconds(STEP_TIME, TimerMode::Repeating),
+        }
+    }
+
+    fn set_speed(&mut self, speed: usize)
+    {
+        self.speed = speed.min(SPEEDS.len() - 1);
+        self.timer.set_duration(std::time::Duration::from_secs_f32(STEP_TIME / SPEEDS[self.speed]));
+    }
+
+    fn finished(&self) -> bool
+    {
+        self.step >= self.replay.actions.len()
+    }
+
+    fn step_forward(&mut self, engine: &mut GameEngine, table_events: &mut EventWriter<TableEvent>)
+    {
+        let Some(&action) = self.replay.actions.get(self.step) else { return; };
+        match engine.apply(action)
+        {
+            Ok(events) => {
+                table_events.send_batch(events.into_iter().map(TableEvent));
+                self.step += 1;
+            },
+            Err(err) => {
+                warn!("Move {} of a replay: {}", self.step + 1, err);
+                self.paused = true;
+            },
+        }
+    }
+
+    // A finished replay starts over on a table dealt again
+    fn restart(&mut self, engine: &mut GameEngine) -> Result<(), ReplayError>
+    {
+        *engine = self.replay.deal()?;
+        self.step = 0;
+        Ok(())
+    }
+
+    // An engine can't undo an action, the table is dealt again and played up to a move before
+    fn step_back(&mut self, engine: &mut GameEngine)
+    {
+        if self.step == 0 { return; }
+        match self.replay.table_after(self.step - 1)
+        {
+            Ok(table) => {
+                *engine = table;
+                self.step -= 1;
+            },
+            Err(err) => warn!("{}", err),
+        }
+    }
+}
+
+// A table dealt the way a replay was, with controls of a playback in place of a HUD
+fn playback_setup(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    playback: Res<Playback>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    let engine = match playback.replay.deal()
+    {
+        Ok(engine) => engine,
+        Err(err) => {
+            warn!("{}", err);
+            commands.remove_resource::<GameEngine>();
+            commands.insert_resource(MenuMessage(err.to_string()));
+            next_state.set(GameState::Menu);
+            return;
+        },
+    };
+    spawn_table(&mut commands, &asset_server, &engine);
+    commands.insert_resource(engine);
+
+    commands.spawn((
+        TextBundle::from_section(
+            "",
+            TextStyle {
+                font: asset_server.load("fonts/FiraSans-Bold.ttf"),
+                font_size: 24.0,
+                color: TEXT_COLOR,
+            },
+        )
+        .with_style(Style {
+            position_type: PositionType::Absolute,
+            position: UiRect {
+                right: Val::Percent(2.0),
+                top: Val::Percent(3.0),
+                ..default()
+            },
+            ..default()
+        }),
+        PlaybackText,
+        GameItem,
+    ));
+
+    let button_style = Style {
+        size: Size::new(Val::Px(110.0), Val::Px(60.0)),
+        margin: UiRect::all(Val::Px(8.0)),
+        justify_content: JustifyContent::Center,
+        align_items: AlignItems::Center,
+        ..default()
+    };
+    let button_text_style = TextStyle {
+        font: asset_server.load("fonts/Vividly.otf"),
+        font_size: CONTROL_FONT_SIZE,
+        color: TEXT_COLOR,
+    };
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    position: UiRect {
+                        left: Val::Percent(3.0),
+                        bottom: Val::Percent(3.0),
+                        ..default()
+                    },
+                    ..default()
+                },
+                ..default()
+            },
+            GameItem,
+        ))
+        .with_children(|parent| {
+            for (label, action) in [
+                ("Back", PlaybackButtonAction::StepBack),
+                ("Pause", PlaybackButtonAction::Pause),
+                ("Next", PlaybackButtonAction::StepForward),
+                ("Slower", PlaybackButtonAction::Slower),
+                ("Faster", PlaybackButtonAction::Faster),
+                ("Menu", PlaybackButtonAction::Menu),
+            ] {
+                let pause = matches!(action, PlaybackButtonAction::Pause);
+                parent
+                    .spawn((
+                        ButtonBundle {
+                            style: button_style.clone(),
+                            background_color: NORMAL_BUTTON.into(),
+                            ..default()
+                        },
+                        action,
+                    ))
+                    .with_children(|parent| {
+                        let mut text = parent.spawn(TextBundle::from_section(label, button_text_style.clone()));
+                        if pause { text.insert(PauseText); }
+                    });
+            }
+        });
+}
+
+fn playback_action(
+    interaction_q: Query<(&Interaction, &PlaybackButtonAction), ButtonInteraction>,
+    mut playback: ResMut<Playback>,
+    mut engine: ResMut<GameEngine>,
+    mut table_events: EventWriter<TableEvent>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    for (interaction, button_action) in &interaction_q
+    {
+        if *interaction == Interaction::Clicked
+        {
+            control(&mut playback, button_action, &mut engine, &mut table_events, &mut next_state);
+        }
+    }
+}
+
+fn keyboard_action(
+    key: Res<Input<KeyCode>>,
+    mut playback: ResMut<Playback>,
+    mut engine: ResMut<GameEngine>,
+    mut table_events: EventWriter<TableEvent>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    let keys = [
+        (KeyCode::Left, PlaybackButtonAction::StepBack),
+        (KeyCode::Space, PlaybackButtonAction::Pause),
+        (KeyCode::Right, PlaybackButtonAction::StepForward),
+        (KeyCode::Down, PlaybackButtonAction::Slower),
+        (KeyCode::Up, PlaybackButtonAction::Faster),
+        (KeyCode::Escape, PlaybackButtonAction::Menu),
+    ];
+    for (_, action) in keys.iter().filter(|(code, _)| key.just_pressed(*code))
+    {
+        control(&mut playback, action, &mut engine, &mut table_events, &mut next_state);
+    }
+}
+
+// Stepping by hand pauses a playback, so the table stays on the move stepped to
+fn control(
+    playback: &mut Playback,
+    action: &PlaybackButtonAction,
+    engine: &mut GameEngine,
+    table_events: &mut EventWriter<TableEvent>,
+    next_state: &mut NextState<GameState>,
+) {
+    match action
+    {
+        PlaybackButtonAction::StepBack => {
+            playback.paused = true;
+            playback.step_back(engine);
+        },
+        PlaybackButtonAction::Pause => {
+            if playback.paused && playback.finished()
+            {
+                if let Err(err) = playback.restart(engine)
+                {
+                    warn!("{}", err);
+                    next_state.set(GameState::Menu);
+                    return;
+                }
+            }
+            playback.paused = !playback.paused;
+            playback.timer.reset();
+        },
+        PlaybackButtonAction::StepForward => {
+            playback.paused = true;
+            playback.step_forward(engine, table_events);
+        },
+        PlaybackButtonAction::Slower => {
+            let speed = playback.speed.saturating_sub(1);
+            playback.set_speed(speed);
+        },
+        PlaybackButtonAction::Faster => {
+            let speed = playback.speed + 1;
+            playback.set_speed(speed);
+        },
+        PlaybackButtonAction::Menu => next_state.set(GameState::Menu),
+    }
+}
+
+fn play_back(
+    time: Res<Time>,
+    mut playback: ResMut<Playback>,
+    mut engine: ResMut<GameEngine>,
+    mut table_events: EventWriter<TableEvent>,
+) {
+    if playback.paused { return; }
+    if playback.finished()
+    {
+        playback.paused = true;
+        return;
+    }
+    if playback.timer.tick(time.delta()).just_finished()
+    {
+        playback.step_forward(&mut engine, &mut table_events);
+    }
+}
+
+fn show_playback(
+    playback: Res<Playback>,
+    mut text_q: Query<&mut Text, (With<PlaybackText>, Without<PauseText>)>,
+    mut pause_q: Query<&mut Text, With<PauseText>>,
+) {
+    let replay = &playback.replay;
+    for mut text in text_q.iter_mut()
+    {
+        text.sections[0].value = format!(
+            "Replay of round {}, seed {}\nMove {} of {}, speed x{}",
+            replay.round, replay.match_seed, playback.step, replay.actions.len(), SPEEDS[playback.speed]
+        );
+    }
+    for mut text in pause_q.iter_mut()
+    {
+        text.sections[0].value = if playback.paused { "Play" } else { "Pause" }.to_string();
+    }
+}
@@ -0,0 +1,162 @@
+use std::fmt;
+use std::io;
+use serde::{Deserialize, Serialize};
+use crate::engine::{AfterDraw, RuleError, Rules};
+use crate::storage;
+
+// Presets are kept in a RON file that can be edited by hand, next to other settings
+pub const PRESETS_FILE: &str = "presets.ron";
+// Name of the preset the rules set up in the menu are saved as
+pub const CUSTOM_PRESET: &str = "Custom";
+
+// Every rule flag under one name
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+pub struct Preset
+{
+    pub name: String,
+    pub rules: Rules,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+pub struct RulePresets
+{
+    presets: Vec<Preset>,
+}
+
+#[derive(Debug)]
+pub enum PresetError
+{
+    Io(io::Error),
+    Parse(ron::error::SpannedError),
+    Write(ron::Error),
+}
+
+impl RulePresets
+{
+    pub fn from_ron(text: &str) -> Result<Self, PresetError>
+    {
+        ron::from_str(text).map_err(PresetError::Parse)
+    }
+
+    pub fn to_ron(&self) -> Result<String, PresetError>
+    {
+        ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default()).map_err(PresetError::Write)
+    }
+
+    pub fn load() -> Result<Self, PresetError>
+    {
+        let text = storage::read(PRESETS_FILE).map_err(PresetError::Io)?;
+        Self::from_ron(&text)
+    }
+
+    pub fn save(&self) -> Result<(), PresetError>
+    {
+        storage::write(PRESETS_FILE, &self.to_ron()?).map_err(PresetError::Io)
+    }
+
+    pub fn presets(&self) -> &[Preset]
+    {
+        &self.presets
+    }
+
+    // Presets edited into rules that can't be played get the default rules. Names of them
+    // are returned with what was wrong
+    pub fn reset_invalid(&mut self) -> Vec<(String, RuleError)>
+    {
+        let mut reset = Vec::new();
+        for preset in self.presets.iter_mut()
+        {
+            if let Err(err) = preset.rules.validate()
+            {
+                reset.push((preset.name.clone(), err));
+                preset.rules = Rules::default();
+            }
+        }
+        reset
+    }
+
+    // First preset with exactly these rules
+    pub fn find(&self, rules: &Rules) -> Option<&Preset>
+    {
+        self.presets.iter().find(|preset| preset.rules == *rules)
+    }
+
+    // Replaces an earlier custom preset, there is only one
+    pub fn save_custom(&mut self, rules: Rules)
+    {
+        match self.presets.iter_mut().find(|preset| preset.name == CUSTOM_PRESET)
+        {
+            Some(preset) => preset.rules = rules,
+            None => self.presets.push(Preset { name: CUSTOM_PRESET.to_string(), rules }),
+        }
+    }
+
+    // One line naming the preset the rules come from and the variants they play with
+    pub fn summary(&self, rules: &Rules) -> String
+    {
+        let name = self.find(rules).map_or(CUSTOM_PRESET, |preset| preset.name.as_str());
+        let mut summary = format!("{}: {} players, to {} points", name, rules.num_players, rules.target_score);
+        let variants = [
+            (rules.dos, "DOS"),
+            (rules.stackable_cards, "stacking"),
+            (rules.progressive, "progressive"),
+            (rules.draw_until_playable, "draw to match"),
+            (rules.seven_o, "7-0"),
+            (rules.jump_in, "jump-in"),
+            (rules.turbo, "turbo"),
+        ];
+        for (_, variant) in variants.iter().filter(|(on, _)| *on)
+        {
+            summary.push_str(", ");
+            summary.push_str(variant);
+        }
+        summary
+    }
+}
+
+// Presets a file is started with
+impl Default for RulePresets {
+    fn default() -> Self {
+        let classic = Rules {
+            num_players: 4,
+            wild_draw4_challenge: true,
+            ..Rules::default()
+        };
+        let party = Rules {
+            num_players: 6,
+            stackable_cards: true,
+            stack_draw4: true,
+            draw_until_playable: true,
+            seven_o: true,
+            jump_in: true,
+            target_score: 300,
+            ..Rules::default()
+        };
+        let speed = Rules {
+            num_players: 4,
+            turbo: true,
+            turn_time: 5,
+            after_draw: AfterDraw::MustPlayDrawn,
+            target_score: 200,
+            hand_size: 5,
+            catch_delay: 1000,
+            ..Rules::default()
+        };
+        RulePresets {
+            presets: [("Classic", classic), ("Party", party), ("Speed", speed)]
+                .into_iter()
+                .map(|(name, rules)| Preset { name: name.to_string(), rules })
+                .collect(),
+        }
+    }
+}
+
+impl fmt::Display for PresetError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PresetError::Io(err) => write!(f, "Presets can't be read or written: {}", err),
+            PresetError::Parse(err) => write!(f, "Presets file is malformed: {}", err),
+            PresetError::Write(err) => write!(f, "Presets can't be written out: {}", err),
+        }
+    }
+}
@@ -0,0 +1,124 @@
+use std::fmt;
+use std::io;
+use rand::{rngs::StdRng, SeedableRng};
+use serde::{Deserialize, Serialize};
+use crate::engine::{Action, GameEngine, RuleError, Rules};
+use crate::storage;
+
+// Replays are kept in a RON file next to other settings
+pub const REPLAYS_FILE: &str = "replays.ron";
+// Older rounds are dropped once there are more replays than this
+pub const MAX_REPLAYS: usize = 10;
+
+// A round as it was played: a deck shuffled with `seed` is dealt by `rules`, then every
+// action follows in order
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+pub struct Replay
+{
+    pub rules: Rules,
+    pub seed: u64,
+    // Seed of the match and number of the round in it, to tell replays apart
+    pub match_seed: u64,
+    pub round: usize,
+    pub actions: Vec<Action>,
+}
+
+// Latest replays, oldest first
+#[derive(Debug, PartialEq, Eq, Clone, Default, Serialize, Deserialize)]
+pub struct Replays
+{
+    replays: Vec<Replay>,
+}
+
+#[derive(Debug)]
+pub enum ReplayError
+{
+    Io(io::Error),
+    Parse(ron::error::SpannedError),
+    Write(ron::Error),
+    // Rules of a replay don't deal, or an action doesn't follow, likely after the rules changed
+    Rules(RuleError),
+    Action(usize, RuleError),
+}
+
+impl Replay
+{
+    // Round played so far on a table dealt with `seed`
+    pub fn record(engine: &GameEngine, seed: u64, match_seed: u64, round: usize) -> Self
+    {
+        Replay { rules: *engine.rules(), seed, match_seed, round, actions: engine.history().to_vec() }
+    }
+
+    pub fn deal(&self) -> Result<GameEngine, ReplayError>
+    {
+        GameEngine::new(self.rules, &mut StdRng::seed_from_u64(self.seed)).map_err(ReplayError::Rules)
+    }
+
+    // Table as it was after the first `steps` actions
+    pub fn table_after(&self, steps: usize) -> Result<GameEngine, ReplayError>
+    {
+        let mut engine = self.deal()?;
+        for (step, &action) in self.actions.iter().take(steps).enumerate()
+        {
+            engine.apply(action).map_err(|err| ReplayError::Action(step, err))?;
+        }
+        Ok(engine)
+    }
+
+    pub fn summary(&self) -> String
+    {
+        format!(
+            "Seed {}, round {}: {} players, {} moves",
+            self.match_seed, self.round, self.rules.num_players, self.actions.len()
+        )
+    }
+}
+
+impl Replays
+{
+    pub fn from_ron(text: &str) -> Result<Self, ReplayError>
+    {
+        ron::from_str(text).map_err(ReplayError::Parse)
+    }
+
+    pub fn to_ron(&self) -> Result<String, ReplayError>
+    {
+        ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default()).map_err(ReplayError::Write)
+    }
+
+    pub fn load() -> Result<Self, ReplayError>
+    {
+        let text = storage::read(REPLAYS_FILE).map_err(ReplayError::Io)?;
+        Self::from_ron(&text)
+    }
+
+    pub fn save(&self) -> Result<(), ReplayError>
+    {
+        storage::write(REPLAYS_FILE, &self.to_ron()?).map_err(ReplayError::Io)
+    }
+
+    pub fn replays(&self) -> &[Replay]
+    {
+        &self.replays
+    }
+
+    // Keeps only the latest MAX_REPLAYS
+    pub fn push(&mut self, replay: Replay)
+    {
+        self.replays.push(replay);
+        let extra = self.replays.len().saturating_sub(MAX_REPLAYS);
+        self.replays.drain(..extra);
+    }
+}
+
+impl fmt::Display for ReplayError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ReplayError::Io(err) => write!(f, "Replays can't be read or written: {}", err),
+            ReplayError::Parse(err) => write!(f, "Replays file is malformed: {}", err),
+            ReplayError::Write(err) => write!(f, "Replays can't be written out: {}", err),
+            ReplayError::Rules(err) => write!(f, "Replay can't be dealt: {}", err),
+            ReplayError::Action(step, err) => write!(f, "Move {} of a replay is not allowed: {}", step + 1, err),
+        }
+    }
+}
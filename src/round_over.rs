@@ -0,0 +1,221 @@
+use bevy::prelude::*;
+use crate::{despawn_screen, ButtonInteraction, GameState, Rules};
+use crate::engine::{GameEngine, Scoreboard};
+use crate::game::{card_image_name, seat_name};
+use crate::game_ui::button_colors;
+use crate::menu::{BG_COLOR, TITLE_COLOR, TEXT_COLOR, NORMAL_BUTTON, FONT_SIZE};
+
+const OVERLAY_COLOR: Color = Color::rgba(0.0, 0.0, 0.0, 0.6);
+const RESULT_CARD_SIZE: Vec2 = Vec2::new(35.0, 49.0);
+
+// Tag component used to tag entities added on the results screen
+#[derive(Component)]
+struct OnRoundOverScreen;
+
+#[derive(Component)]
+enum RoundOverButtonAction {
+    NextRound,
+    NewMatch,
+    Menu,
+}
+
+pub struct RoundOverPlugin;
+
+impl Plugin for RoundOverPlugin
+{
+    fn build(&self, app: &mut App)
+    {
+        app.add_systems((record_scores, results_setup).chain().in_schedule(OnEnter(GameState::RoundOver)))
+            .add_systems((round_over_action, keyboard_action, button_colors).in_set(OnUpdate(GameState::RoundOver)))
+            .add_system(despawn_screen::<OnRoundOverScreen>.in_schedule(OnExit(GameState::RoundOver)));
+    }
+}
+
+fn record_scores(
+    engine: Res<GameEngine>,
+    mut scoreboard: ResMut<Scoreboard>,
+) {
+    scoreboard.record(&engine);
+}
+
+// Remaining cards and points of every seat, over a table as it was left
+fn results_setup(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    engine: Res<GameEngine>,
+    rules: Res<Rules>,
+    scoreboard: Res<Scoreboard>,
+) {
+    let font = asset_server.load("fonts/Vividly.otf");
+    let button_style = Style {
+        size: Size::new(Val::Px(220.0), Val::Px(65.0)),
+        margin: UiRect::all(Val::Px(20.0)),
+        justify_content: JustifyContent::Center,
+        align_items: AlignItems::Center,
+        ..default()
+    };
+    let text_style = TextStyle {
+        font: font.clone(),
+        font_size: FONT_SIZE,
+        color: TEXT_COLOR,
+    };
+    let winner = engine.winner().expect("round ended without a winner");
+    let match_winner = scoreboard.match_winner(rules.target_score);
+    let title = match match_winner
+    {
+        Some(seat) => format!("{} wins the match!", seat_name(seat)),
+        None => format!("{} wins round {}!", seat_name(winner), scoreboard.rounds()),
+    };
+    let rematch = match match_winner
+    {
+        Some(_) => ("New match", RoundOverButtonAction::NewMatch),
+        None => ("Next round", RoundOverButtonAction::NextRound),
+    };
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    size: Size::new(Val::Percent(100.0), Val::Percent(100.0)),
+                    align_items: AlignItems::Center,
+                    justify_content: JustifyContent::Center,
+                    ..default()
+                },
+                background_color: OVERLAY_COLOR.into(),
+                ..default()
+            },
+            OnRoundOverScreen,
+        ))
+        .with_children(|parent| {
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        flex_direction: FlexDirection::Column,
+                        align_items: AlignItems::Center,
+                        padding: UiRect::all(Val::Px(30.0)),
+                        ..default()
+                    },
+                    background_color: BG_COLOR.into(),
+                    ..default()
+                })
+                .with_children(|parent| {
+                    parent.spawn(
+                        TextBundle::from_section(
+                            title,
+                            TextStyle {
+                                font: font.clone(),
+                                font_size: 80.0,
+                                color: TITLE_COLOR,
+                            },
+                        )
+                        .with_style(Style {
+                            margin: UiRect::all(Val::Px(30.0)),
+                            ..default()
+                        }),
+                    );
+                    parent.spawn(
+                        TextBundle::from_section(
+                            format!("+{} points, playing to {}", engine.round_points(), rules.target_score),
+                            text_style.clone(),
+                        )
+                        .with_style(Style {
+                            margin: UiRect::bottom(Val::Px(20.0)),
+                            ..default()
+                        }),
+                    );
+
+                    // One row per seat with its score and the cards left in its hand
+                    for seat in 0..engine.num_seats()
+                    {
+                        parent
+                            .spawn(NodeBundle {
+                                style: Style {
+                                    size: Size::new(Val::Px(600.0), Val::Auto),
+                                    align_items: AlignItems::Center,
+                                    margin: UiRect::all(Val::Px(4.0)),
+                                    ..default()
+                                },
+                                ..default()
+                            })
+                            .with_children(|parent| {
+                                parent.spawn(
+                                    TextBundle::from_section(
+                                        format!("{}: {}", seat_name(seat), scoreboard.scores()[seat]),
+                                        text_style.clone(),
+                                    )
+                                    .with_style(Style {
+                                        size: Size::new(Val::Px(260.0), Val::Auto),
+                                        ..default()
+                                    }),
+                                );
+                                for card in engine.hand(seat)
+                                {
+                                    parent.spawn(ImageBundle {
+                                        style: Style {
+                                            size: Size::new(Val::Px(RESULT_CARD_SIZE.x), Val::Px(RESULT_CARD_SIZE.y)),
+                                            margin: UiRect::all(Val::Px(2.0)),
+                                            ..default()
+                                        },
+                                        image: asset_server.load(card_image_name(card)).into(),
+                                        ..default()
+                                    });
+                                }
+                            });
+                    }
+
+                    parent
+                        .spawn(NodeBundle::default())
+                        .with_children(|parent| {
+                            for (label, action) in [rematch, ("Menu", RoundOverButtonAction::Menu)]
+                            {
+                                parent
+                                    .spawn((
+                                        ButtonBundle {
+                                            style: button_style.clone(),
+                                            background_color: NORMAL_BUTTON.into(),
+                                            ..default()
+                                        },
+                                        action,
+                                    ))
+                                    .with_children(|parent| {
+                                        parent.spawn(TextBundle::from_section(label, text_style.clone()));
+                                    });
+                            }
+                        });
+                });
+        });
+}
+
+// Another round is dealt with the same Rules, since they are only read when a game is set up.
+// After the last round of a match the game starts a new one, scores and seed included
+fn round_over_action(
+    interaction_q: Query<(&Interaction, &RoundOverButtonAction), ButtonInteraction>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    for (interaction, button_action) in &interaction_q
+    {
+        if *interaction == Interaction::Clicked
+        {
+            match button_action
+            {
+                RoundOverButtonAction::NextRound | RoundOverButtonAction::NewMatch => next_state.set(GameState::Game),
+                RoundOverButtonAction::Menu => next_state.set(GameState::Menu),
+            }
+        }
+    }
+}
+
+fn keyboard_action(
+    key: Res<Input<KeyCode>>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    if key.just_pressed(KeyCode::Return)
+    {
+        next_state.set(GameState::Game);
+    }
+
+    if key.just_pressed(KeyCode::Escape)
+    {
+        next_state.set(GameState::Menu);
+    }
+}
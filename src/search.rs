@@ -0,0 +1,93 @@
+use std::time::Duration;
+use instant::Instant;
+use rand::RngCore;
+use crate::bots::{BotStrategy, BotView, NormalBot};
+use crate::engine::{Action, GameEngine, Suit};
+
+// Time a Master bot thinks about a move, unless it is given another one
+pub const SEARCH_BUDGET: Duration = Duration::from_millis(800);
+// Rounds of rollouts a Master bot makes when a match has to go the same way for a seed
+pub const SEARCH_ROUNDS: usize = 1000;
+// Moves after which a rollout is cut short when nobody has won yet
+const ROLLOUT_MOVES: usize = 300;
+
+// Tries every legal move on tables dealt as the seat imagines them, with hidden cards taken
+// from the deck and other hands. Each is played out to the end by Normal bots and the move
+// that won most often is picked, once the time budget runs out
+pub struct SearchBot
+{
+    pub budget: Duration,
+    // Number of rounds to make in place of the budget, the same move comes out of the same
+    // numbers however fast a machine is
+    pub rounds: Option<usize>,
+}
+
+impl BotStrategy for SearchBot
+{
+    fn pick_card(&self, view: &BotView, playable: &[usize], rng: &mut dyn RngCore) -> usize
+    {
+        NormalBot.pick_card(view, playable, rng)
+    }
+
+    fn pick_color(&self, view: &BotView, rng: &mut dyn RngCore) -> Suit
+    {
+        NormalBot.pick_color(view, rng)
+    }
+
+    fn challenge(&self, view: &BotView, rng: &mut dyn RngCore) -> bool
+    {
+        NormalBot.challenge(view, rng)
+    }
+
+    fn action(&self, view: &BotView, rng: &mut dyn RngCore) -> Action
+    {
+        let actions = view.legal_actions();
+        if actions.len() < 2
+        {
+            return actions.first().copied().unwrap_or_else(|| NormalBot.action(view, rng));
+        }
+
+        // Every move gets the same number of rollouts, one round at a time
+        let deadline = Instant::now() + self.budget;
+        let mut wins = vec![0.0; actions.len()];
+        let mut rounds = 0;
+        loop
+        {
+            for (action, wins) in actions.iter().zip(wins.iter_mut())
+            {
+                let mut table = view.imagine(rng);
+                if table.apply(*action).is_ok()
+                {
+                    *wins += rollout(table, view.seat(), rng);
+                }
+            }
+            rounds += 1;
+            let done = match self.rounds
+            {
+                Some(limit) => rounds >= limit,
+                None => Instant::now() >= deadline,
+            };
+            if done { break; }
+        }
+        let best = (0..actions.len()).max_by(|&a, &b| wins[a].total_cmp(&wins[b])).unwrap();
+        actions[best]
+    }
+}
+
+// 1 when the seat wins a game played out from a table, half of it when the game takes too
+// long but the seat has the smallest hand by then
+fn rollout(mut table: GameEngine, seat: usize, rng: &mut dyn RngCore) -> f64
+{
+    for _ in 0..ROLLOUT_MOVES
+    {
+        if let Some(winner) = table.winner()
+        {
+            return if winner == seat { 1.0 } else { 0.0 };
+        }
+        let turn = table.turn();
+        let action = NormalBot.action(&BotView::new(&table, turn), rng);
+        if table.apply(action).is_err() { break; }
+    }
+    let smallest = (0..table.num_seats()).all(|other| table.hand(seat).len() <= table.hand(other).len());
+    if smallest { 0.5 } else { 0.0 }
+}
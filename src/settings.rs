@@ -0,0 +1,62 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use crate::{DisplayQuality, Rules};
+use crate::storage;
+
+const SETTINGS_FILE: &str = "settings.ron";
+
+// Everything the menu changes, kept between launches
+#[derive(Default, Serialize, Deserialize)]
+#[serde(default)]
+struct Settings
+{
+    rules: Rules,
+    display_quality: DisplayQuality,
+}
+
+pub struct SettingsPlugin;
+
+impl Plugin for SettingsPlugin
+{
+    fn build(&self, app: &mut App)
+    {
+        let settings = load_settings();
+        app.insert_resource(settings.rules)
+            .insert_resource(settings.display_quality)
+            .add_system(save_settings.run_if(resource_changed::<Rules>().or_else(resource_changed::<DisplayQuality>())));
+    }
+}
+
+// Defaults are used until something is saved, or when saved settings can't be parsed.
+// Saved rules that can't be played are replaced by the default ones
+fn load_settings() -> Settings
+{
+    let mut settings = match storage::read(SETTINGS_FILE)
+    {
+        Ok(text) => ron::from_str(&text).unwrap_or_else(|err| {
+            warn!("Saved settings are malformed, defaults are used instead: {}", err);
+            Settings::default()
+        }),
+        Err(_) => Settings::default(),
+    };
+    if let Err(err) = settings.rules.validate()
+    {
+        warn!("Saved rules can't be played, defaults are used instead: {}", err);
+        settings.rules = Rules::default();
+    }
+    settings
+}
+
+fn save_settings(
+    rules: Res<Rules>,
+    display_quality: Res<DisplayQuality>,
+) {
+    let settings = Settings { rules: *rules, display_quality: *display_quality };
+    let saved = ron::ser::to_string_pretty(&settings, ron::ser::PrettyConfig::default())
+        .map_err(|err| err.to_string())
+        .and_then(|text| storage::write(SETTINGS_FILE, &text).map_err(|err| err.to_string()));
+    if let Err(err) = saved
+    {
+        warn!("Settings can't be saved: {}", err);
+    }
+}
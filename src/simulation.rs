@@ -0,0 +1,235 @@
+use std::time::Duration;
+use rand::{rngs::StdRng, Rng, RngCore, SeedableRng};
+use crate::bots::{BotLevel, BotStrategy, BotView};
+use crate::engine::{Action, Event, GameEngine, Rules};
+use crate::presets::RulePresets;
+use crate::search::{SearchBot, SEARCH_ROUNDS};
+
+// Same as in the game, a bot forgets to call its last card now and then
+const FORGET_CALL_CHANCE: f64 = 0.2;
+// Actions after which a round is given up as endless
+const MAX_ACTIONS: usize = 5000;
+
+pub const USAGE: &str = "\
+Usage: simulate [options]
+    --games N       rounds to play (1000)
+    --players N     seats at the table, overrides the rules, has to match --bots if both are given
+    --preset NAME   rules of a preset, from presets.ron or the defaults
+    --rules FILE    rules from a RON file
+    --bots LEVELS   a letter per seat out of E, N, H and M (N for every seat)
+    --think MS      time a Master bot thinks about a move without --seed (10)
+    --seed N        seed for dealing and bots, random by default. Master bots search a fixed
+                    number of rounds with it, so a run plays out the same on any machine
+    --csv           print CSV instead of a table";
+
+#[derive(Debug)]
+pub struct Options
+{
+    pub games: usize,
+    pub rules: Rules,
+    pub bots: Vec<BotLevel>,
+    pub think: Duration,
+    pub seed: u64,
+    // The seed was given, runs have to play out the same again
+    pub seeded: bool,
+    pub csv: bool,
+}
+
+// Sums over all rounds played
+#[derive(Debug, Default)]
+pub struct Stats
+{
+    pub games: usize,
+    pub unfinished: usize,
+    pub turns: usize,
+    pub cards_drawn: usize,
+    pub first_seat_wins: usize,
+    // By bot of the options, not by seat
+    pub wins: Vec<usize>,
+    // Rounds where a bot tried an action the engine refused, they are left out of the sums
+    pub failed: Vec<FailedRound>,
+}
+
+// A round can be played again alone with its seed, bots seated as in round `game`
+#[derive(Debug)]
+pub struct FailedRound
+{
+    pub game: usize,
+    pub seed: u64,
+    pub error: String,
+}
+
+// How a single round went
+#[derive(Debug, Default)]
+struct Round
+{
+    // Bot of the options who won, none when the round was given up
+    winner: Option<usize>,
+    first_seat_won: bool,
+    turns: usize,
+    cards_drawn: usize,
+}
+
+pub fn parse_options(mut args: impl Iterator<Item = String>) -> Result<Options, String>
+{
+    let mut games = 1000;
+    let mut rules = Rules::default();
+    let mut players = None;
+    let mut bots = None;
+    let mut think = 10;
+    let mut seed = None;
+    let mut csv = false;
+
+    while let Some(arg) = args.next()
+    {
+        let mut value = || args.next().ok_or_else(|| format!("{} needs a value", arg));
+        match arg.as_str()
+        {
+            "--games" => games = number(&value()?)?,
+            "--players" => players = Some(number(&value()?)?),
+            "--preset" => {
+                let name = value()?;
+                let presets = RulePresets::load().unwrap_or_default();
+                let preset = presets.presets().iter().find(|preset| preset.name.eq_ignore_ascii_case(&name));
+                rules = preset.ok_or_else(|| format!("There is no preset {}", name))?.rules;
+            },
+            "--rules" => {
+                let path = value()?;
+                let text = std::fs::read_to_string(&path).map_err(|err| format!("{}: {}", path, err))?;
+                rules = ron::from_str(&text).map_err(|err| format!("{}: {}", path, err))?;
+            },
+            "--bots" => bots = Some(value()?.chars().map(level).collect::<Result<Vec<_>, _>>()?),
+            "--think" => think = number(&value()?)?,
+            "--seed" => seed = Some(number(&value()?)? as u64),
+            "--csv" => csv = true,
+            _ => return Err(format!("Unknown option {}", arg)),
+        }
+    }
+
+    // Without --players there are as many seats as bots given
+    if let Some(players) = players.or(bots.as_ref().map(Vec::len)) { rules.num_players = players; }
+    let bots = bots.unwrap_or_else(|| vec![BotLevel::Normal; rules.num_players]);
+    if bots.len() != rules.num_players
+    {
+        return Err(format!("{} bots given for {} players", bots.len(), rules.num_players));
+    }
+    rules.validate().map_err(|err| err.to_string())?;
+    let think = Duration::from_millis(think as u64);
+    let seeded = seed.is_some();
+    let seed = seed.unwrap_or_else(|| rand::thread_rng().gen());
+    Ok(Options { games, rules, bots, think, seed, seeded, csv })
+}
+
+fn number(text: &str) -> Result<usize, String>
+{
+    text.parse().map_err(|_| format!("{} is not a number", text))
+}
+
+fn level(letter: char) -> Result<BotLevel, String>
+{
+    match letter.to_ascii_uppercase()
+    {
+        'E' => Ok(BotLevel::Easy),
+        'N' => Ok(BotLevel::Normal),
+        'H' => Ok(BotLevel::Hard),
+        'M' => Ok(BotLevel::Master),
+        _ => Err(format!("{} is not a bot level", letter)),
+    }
+}
+
+// Every round gets a seed of its own out of the seed of the options
+pub fn simulate(options: &Options) -> Stats
+{
+    let mut rng = StdRng::seed_from_u64(options.seed);
+    let mut stats = Stats { wins: vec![0; options.bots.len()], ..Stats::default() };
+    for game in 0..options.games
+    {
+        let seed = rng.gen();
+        match play_round(options, game, &mut StdRng::seed_from_u64(seed))
+        {
+            Ok(round) => {
+                stats.games += 1;
+                stats.turns += round.turns;
+                stats.cards_drawn += round.cards_drawn;
+                if round.first_seat_won { stats.first_seat_wins += 1; }
+                match round.winner
+                {
+                    Some(bot) => stats.wins[bot] += 1,
+                    None => stats.unfinished += 1,
+                }
+            },
+            Err(error) => stats.failed.push(FailedRound { game, seed, error }),
+        }
+    }
+    stats
+}
+
+// Seat `seat` is taken by bot `(seat + game) % bots` of the options
+fn play_round(options: &Options, game: usize, rng: &mut StdRng) -> Result<Round, String>
+{
+    let seats = options.bots.len();
+    let bot_of = |seat: usize| (seat + game) % seats;
+    let search = SearchBot { budget: options.think, rounds: options.seeded.then_some(SEARCH_ROUNDS) };
+    let mut engine = GameEngine::new(options.rules, rng).map_err(|err| err.to_string())?;
+    let mut round = Round::default();
+
+    for _ in 0..MAX_ACTIONS
+    {
+        if let Some(winner) = engine.winner()
+        {
+            round.winner = Some(bot_of(winner));
+            round.first_seat_won = winner == 0;
+            return Ok(round);
+        }
+
+        let seat = engine.turn();
+        let view = BotView::new(&engine, seat);
+        let action = match options.bots[bot_of(seat)]
+        {
+            BotLevel::Master => search.action(&view, rng),
+            level => level.strategy().action(&view, rng),
+        };
+        apply(&mut engine, action, rng, &mut round)?;
+        if options.rules.jump_in { jump_in(&mut engine, rng, &mut round)?; }
+    }
+    Ok(round)
+}
+
+// Any seat but the one whose turn it is puts an identical card on the pile, as bots do in game
+fn jump_in(engine: &mut GameEngine, rng: &mut StdRng, round: &mut Round) -> Result<(), String>
+{
+    if engine.winner().is_some() { return Ok(()); }
+    let jump_in = (0..engine.num_seats())
+        .filter(|&seat| seat != engine.turn())
+        .find_map(|seat| engine.hand(seat).iter().position(|card| engine.can_jump_in(card)).map(|index| (seat, index)));
+    match jump_in
+    {
+        Some((seat, index)) => apply(engine, Action::Play { seat, index }, rng, round),
+        None => Ok(()),
+    }
+}
+
+// Apply an action and count what happened. A seat left with a card to call calls it, unless
+// it forgets and gets caught by the next seat
+fn apply(engine: &mut GameEngine, action: Action, rng: &mut dyn RngCore, round: &mut Round) -> Result<(), String>
+{
+    let seat = action.seat();
+    let mut events = engine.apply(action).map_err(|err| format!("{:?} is not allowed: {}", action, err))?;
+    if engine.uncalled() == Some(seat) && engine.winner().is_none()
+    {
+        let catch = Action::Catch { seat: engine.next_seat(seat), target: seat };
+        let call = if rng.gen_bool(FORGET_CALL_CHANCE) && catch.seat() != seat { catch } else { Action::Call { seat } };
+        events.extend(engine.apply(call).unwrap_or_default());
+    }
+
+    for event in events
+    {
+        match event
+        {
+            Event::TurnChanged { .. } => round.turns += 1,
+            Event::CardDrawn { .. } => round.cards_drawn += 1,
+            _ => (),
+        }
+    }
+    Ok(())
+}
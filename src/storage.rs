@@ -0,0 +1,59 @@
+use std::io;
+
+// Texts kept between launches under a name. On native every one is a file in the user's
+// config directory, in a browser it is an entry of localStorage
+#[cfg(not(target_family = "wasm"))]
+const CONFIG_DIR: &str = "dos";
+#[cfg(target_family = "wasm")]
+const STORAGE_PREFIX: &str = "dos/";
+
+#[cfg(not(target_family = "wasm"))]
+fn path(name: &str) -> io::Result<std::path::PathBuf>
+{
+    dirs::config_dir()
+        .map(|dir| dir.join(CONFIG_DIR).join(name))
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "there is no config directory"))
+}
+
+#[cfg(not(target_family = "wasm"))]
+pub fn read(name: &str) -> io::Result<String>
+{
+    std::fs::read_to_string(path(name)?)
+}
+
+#[cfg(not(target_family = "wasm"))]
+pub fn write(name: &str, text: &str) -> io::Result<()>
+{
+    let path = path(name)?;
+    if let Some(dir) = path.parent()
+    {
+        std::fs::create_dir_all(dir)?;
+    }
+    std::fs::write(path, text)
+}
+
+#[cfg(target_family = "wasm")]
+fn local_storage() -> io::Result<web_sys::Storage>
+{
+    web_sys::window()
+        .and_then(|window| window.local_storage().ok().flatten())
+        .ok_or_else(|| io::Error::new(io::ErrorKind::Unsupported, "localStorage is not available"))
+}
+
+#[cfg(target_family = "wasm")]
+pub fn read(name: &str) -> io::Result<String>
+{
+    local_storage()?
+        .get_item(&format!("{}{}", STORAGE_PREFIX, name))
+        .ok()
+        .flatten()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, format!("{} is not stored", name)))
+}
+
+#[cfg(target_family = "wasm")]
+pub fn write(name: &str, text: &str) -> io::Result<()>
+{
+    local_storage()?
+        .set_item(&format!("{}{}", STORAGE_PREFIX, name), text)
+        .map_err(|_| io::Error::new(io::ErrorKind::Other, format!("{} can't be stored", name)))
+}
@@ -0,0 +1,212 @@
+use dos_game::bots::*;
+use dos_game::engine::*;
+use dos_game::search::SearchBot;
+use std::time::Duration;
+use rand::{rngs::StdRng, SeedableRng};
+
+fn card(rank: Rank, suite: Suit, id: usize) -> Card
+{
+    Card { rank, suite, id }
+}
+
+// Main player holds `hand` over a Red Two, the next seat is down to `next_hand` Blue Ones
+fn table(hand: Vec<Card>, next_hand: usize) -> GameEngine
+{
+    let next = (0..next_hand).map(|i| card(Rank::One, Suit::Blue, 20 + i)).collect();
+    let hands = vec![hand, next, vec![card(Rank::Nine, Suit::Green, 40), card(Rank::Nine, Suit::Green, 41)]];
+    let deck = (0..10).map(|i| card(Rank::Eight, Suit::Blue, 100 + i)).collect();
+    GameEngine::with_cards(Rules { num_players: 3, ..Rules::default() }, hands, vec![card(Rank::Two, Suit::Red, 99)], deck)
+}
+
+#[test]
+fn every_level_plays_rounds_to_the_end_with_legal_actions()
+{
+    for level in [BotLevel::Easy, BotLevel::Normal, BotLevel::Hard]
+    {
+        let mut rng = StdRng::seed_from_u64(3);
+        let rules = Rules { num_players: 4, last_call: false, ..Rules::default() };
+        let mut engine = GameEngine::new(rules, &mut rng).unwrap();
+        for _ in 0..2000
+        {
+            if engine.winner().is_some() { break; }
+            let seat = engine.turn();
+            let action = level.strategy().action(&BotView::new(&engine, seat), &mut rng);
+            engine.apply(action).unwrap();
+        }
+        assert!(engine.winner().is_some(), "{:?} bots never finished a round", level);
+    }
+}
+
+#[test]
+fn normal_bot_keeps_wild_cards_for_later()
+{
+    let engine = table(vec![card(Rank::Wild, Suit::Black, 0), card(Rank::Five, Suit::Red, 1)], 5);
+    let action = NormalBot.action(&BotView::new(&engine, 0), &mut StdRng::seed_from_u64(0));
+    assert_eq!(action, Action::Play { seat: 0, index: 1 });
+}
+
+#[test]
+fn bots_prefer_a_colored_match_over_a_wild()
+{
+    let hand = vec![
+        card(Rank::WildDraw4, Suit::Black, 0),
+        card(Rank::Wild, Suit::Black, 1),
+        card(Rank::Five, Suit::Red, 2),
+        card(Rank::Eight, Suit::Green, 3),
+        card(Rank::Nine, Suit::Green, 4),
+    ];
+    let engine = table(hand, 5);
+    for level in [BotLevel::Normal, BotLevel::Hard]
+    {
+        let action = level.strategy().action(&BotView::new(&engine, 0), &mut StdRng::seed_from_u64(0));
+        assert_eq!(action, Action::Play { seat: 0, index: 2 }, "{:?} bot played a wild", level);
+    }
+}
+
+#[test]
+fn normal_bot_attacks_a_seat_about_to_win()
+{
+    let hand = vec![card(Rank::Nine, Suit::Red, 0), card(Rank::WildDraw4, Suit::Black, 1)];
+    let mut rng = StdRng::seed_from_u64(0);
+
+    let engine = table(hand.clone(), 5);
+    assert_eq!(NormalBot.action(&BotView::new(&engine, 0), &mut rng), Action::Play { seat: 0, index: 0 });
+    let engine = table(hand, 1);
+    assert_eq!(NormalBot.action(&BotView::new(&engine, 0), &mut rng), Action::Play { seat: 0, index: 1 });
+}
+
+#[test]
+fn normal_bot_sheds_the_most_points_first()
+{
+    let engine = table(vec![card(Rank::Three, Suit::Red, 0), card(Rank::Nine, Suit::Red, 1), card(Rank::Four, Suit::Red, 2)], 5);
+    let action = NormalBot.action(&BotView::new(&engine, 0), &mut StdRng::seed_from_u64(0));
+    assert_eq!(action, Action::Play { seat: 0, index: 1 });
+}
+
+#[test]
+fn bots_draw_when_nothing_fits()
+{
+    let engine = table(vec![card(Rank::Nine, Suit::Green, 0)], 5);
+    for level in [BotLevel::Easy, BotLevel::Normal, BotLevel::Hard]
+    {
+        let action = level.strategy().action(&BotView::new(&engine, 0), &mut StdRng::seed_from_u64(0));
+        assert_eq!(action, Action::Draw { seat: 0 });
+    }
+}
+
+// Nothing fits until the third card off the deck, a Red Three
+fn no_match_table(rules: Rules) -> GameEngine
+{
+    let hands = vec![vec![card(Rank::Nine, Suit::Green, 0)], vec![card(Rank::One, Suit::Blue, 1)]];
+    let deck = vec![card(Rank::Three, Suit::Red, 12), card(Rank::Eight, Suit::Blue, 11), card(Rank::Eight, Suit::Blue, 10)];
+    GameEngine::with_cards(Rules { num_players: 2, ..rules }, hands, vec![card(Rank::Two, Suit::Red, 2)], deck)
+}
+
+#[test]
+fn bots_keep_drawing_with_no_skip_until_a_card_fits()
+{
+    let mut engine = no_match_table(Rules { no_skip: true, ..Rules::default() });
+    let mut rng = StdRng::seed_from_u64(0);
+    for _ in 0..3
+    {
+        let action = NormalBot.action(&BotView::new(&engine, 0), &mut rng);
+        assert_eq!(action, Action::Draw { seat: 0 });
+        engine.apply(action).unwrap();
+    }
+    assert_eq!(NormalBot.action(&BotView::new(&engine, 0), &mut rng), Action::Play { seat: 0, index: 3 });
+}
+
+#[test]
+fn bots_play_the_card_drawn_until_playable()
+{
+    let mut engine = no_match_table(Rules { draw_until_playable: true, ..Rules::default() });
+    let mut rng = StdRng::seed_from_u64(0);
+    engine.apply(NormalBot.action(&BotView::new(&engine, 0), &mut rng)).unwrap();
+    assert_eq!(engine.hand(0).len(), 4);
+    assert_eq!(NormalBot.action(&BotView::new(&engine, 0), &mut rng), Action::Play { seat: 0, index: 3 });
+}
+
+#[test]
+fn hard_bot_picks_a_color_others_are_short_of()
+{
+    // Two Red and two Yellow cards held, but every other Yellow card is already on the pile
+    let hand = vec![
+        card(Rank::Wild, Suit::Black, 0),
+        card(Rank::One, Suit::Red, 1),
+        card(Rank::Two, Suit::Red, 2),
+        card(Rank::Three, Suit::Yellow, 3),
+        card(Rank::Four, Suit::Yellow, 4),
+    ];
+    let hands = vec![hand, vec![card(Rank::One, Suit::Blue, 20)], vec![card(Rank::Nine, Suit::Green, 40)]];
+    let mut pile: Vec<Card> = new_deck(&Rules::default()).into_iter()
+        .filter(|held| held.suite == Suit::Yellow && held.id > 4)
+        .map(|held| Card { id: held.id + 1000, ..held })
+        .collect();
+    pile.push(card(Rank::Two, Suit::Blue, 99));
+    let engine = GameEngine::with_cards(Rules { num_players: 3, ..Rules::default() }, hands, pile, vec![]);
+
+    let view = BotView::new(&engine, 0);
+    assert!(view.unseen(Suit::Yellow) < view.unseen(Suit::Red));
+    assert_eq!(HardBot.pick_color(&view, &mut StdRng::seed_from_u64(0)), Suit::Yellow);
+}
+
+#[test]
+fn bot_view_tells_only_the_drawn_card_is_playable()
+{
+    let rules = Rules { num_players: 2, after_draw: AfterDraw::OnlyDrawn, ..Rules::default() };
+    let hands = vec![vec![card(Rank::Five, Suit::Red, 0)], vec![card(Rank::One, Suit::Blue, 1)]];
+    let mut engine = GameEngine::with_cards(rules, hands, vec![card(Rank::Two, Suit::Red, 2)], vec![card(Rank::Two, Suit::Blue, 3)]);
+    assert_eq!(BotView::new(&engine, 0).playable(), vec![0]);
+
+    engine.apply(Action::Draw { seat: 0 }).unwrap();
+    assert_eq!(BotView::new(&engine, 0).playable(), vec![1]);
+}
+
+#[test]
+fn view_lists_legal_actions()
+{
+    let engine = table(vec![card(Rank::Five, Suit::Red, 0), card(Rank::Nine, Suit::Green, 1)], 5);
+    let actions = BotView::new(&engine, 0).legal_actions();
+    assert_eq!(actions, vec![Action::Draw { seat: 0 }, Action::Play { seat: 0, index: 0 }]);
+}
+
+#[test]
+fn search_bot_plays_the_winning_card()
+{
+    let engine = table(vec![card(Rank::Five, Suit::Red, 0)], 5);
+    let bot = SearchBot { budget: Duration::from_millis(20), rounds: None };
+    let action = bot.action(&BotView::new(&engine, 0), &mut StdRng::seed_from_u64(0));
+    assert_eq!(action, Action::Play { seat: 0, index: 0 });
+}
+
+// Moves of a round between Hard and Master bots, each picked with numbers of the seed and
+// the move. A search without time left stops after its rounds anyway
+fn seeded_round(seed: u64) -> Vec<Action>
+{
+    let rules = Rules { num_players: 4, last_call: false, ..Rules::default() };
+    let mut engine = GameEngine::new(rules, &mut StdRng::seed_from_u64(seed)).unwrap();
+    let search = SearchBot { budget: Duration::ZERO, rounds: Some(3) };
+    for _ in 0..1000
+    {
+        if engine.winner().is_some() { break; }
+        let seat = engine.turn();
+        let view = BotView::new(&engine, seat);
+        let mut rng = move_rng(seed, engine.history().len(), MoveChoice::Action);
+        let action = match seat
+        {
+            0 | 2 => HardBot.action(&view, &mut rng),
+            _ => search.action(&view, &mut rng),
+        };
+        engine.apply(action).unwrap();
+    }
+    engine.history().to_vec()
+}
+
+#[test]
+fn same_seed_plays_the_same_round()
+{
+    let moves = seeded_round(21);
+    assert!(!moves.is_empty());
+    assert_eq!(seeded_round(21), moves);
+    assert_ne!(seeded_round(22), moves);
+}
@@ -0,0 +1,1115 @@
+use dos_game::engine::*;
+use rand::{rngs::StdRng, SeedableRng};
+
+fn card(rank: Rank, suite: Suit, id: usize) -> Card
+{
+    Card { rank, suite, id }
+}
+
+fn rules(num_players: usize) -> Rules
+{
+    Rules { num_players, ..Rules::default() }
+}
+
+// Every seat holds one unplayable Green card and a Red Five, a Red Two is on the pile
+fn table(rules: Rules) -> GameEngine
+{
+    table_with(rules, |_| ())
+}
+
+// Same table, with hands changed by `edit` before the game starts
+fn table_with(rules: Rules, edit: impl FnOnce(&mut Vec<Vec<Card>>)) -> GameEngine
+{
+    let mut hands = (0..rules.num_players)
+        .map(|seat| vec![card(Rank::Nine, Suit::Green, seat * 2), card(Rank::Five, Suit::Red, seat * 2 + 1)])
+        .collect();
+    edit(&mut hands);
+    let deck = (0..10).map(|i| card(Rank::Eight, Suit::Blue, 100 + i)).collect();
+    GameEngine::with_cards(rules, hands, vec![card(Rank::Two, Suit::Red, 99)], deck)
+}
+
+// The main player opens with a Red card of `rank` in place of his Green Nine
+fn table_opening_with(rules: Rules, rank: Rank) -> GameEngine
+{
+    table_with(rules, |hands| hands[0][0] = card(rank, Suit::Red, 50))
+}
+
+#[test]
+fn deals_a_hand_to_every_player()
+{
+    let engine = GameEngine::new(rules(4), &mut StdRng::seed_from_u64(7)).unwrap();
+
+    assert_eq!(engine.num_seats(), 4);
+    for seat in 0..4
+    {
+        assert_eq!(engine.hand(seat).len(), HAND_SIZE);
+    }
+    assert_eq!(engine.discard_pile().len(), 1);
+    assert_eq!(engine.deck().len(), new_deck(&Rules::default()).len() - 4 * HAND_SIZE - 1);
+    assert_eq!(engine.turn(), 0);
+}
+
+#[test]
+fn refuses_to_deal_more_cards_than_a_deck_has()
+{
+    let err = GameEngine::deal(rules(20), new_deck(&Rules::default())).unwrap_err();
+    assert_eq!(err, RuleError::NotEnoughCards);
+}
+
+#[test]
+fn refuses_rules_that_leave_no_colored_card_to_start_with()
+{
+    // Only wild cards are left without copies of colored ranks
+    let rules = Rules { rank_copies: 0, ..rules(2) };
+    assert_eq!(rules.validate(), Err(RuleError::NotEnoughCards));
+    assert_eq!(GameEngine::new(rules, &mut StdRng::seed_from_u64(1)).unwrap_err(), RuleError::NotEnoughCards);
+}
+
+#[test]
+fn refuses_to_deal_without_cards_for_a_centre_row()
+{
+    let deck = vec![card(Rank::One, Suit::Red, 0), card(Rank::Two, Suit::Red, 1), card(Rank::Three, Suit::Red, 2)];
+    let err = GameEngine::deal(Rules { dos: true, hand_size: 1, ..rules(2) }, deck.clone()).unwrap_err();
+    assert_eq!(err, RuleError::NotEnoughCards);
+    assert!(GameEngine::deal(Rules { hand_size: 1, ..rules(2) }, deck).is_ok());
+}
+
+#[test]
+fn refuses_more_players_than_seats()
+{
+    assert_eq!(rules(MAX_PLAYERS).validate(), Ok(()));
+    assert_eq!(rules(MAX_PLAYERS + 1).validate(), Err(RuleError::TooManyPlayers));
+    assert_eq!(rules(0).validate(), Err(RuleError::NoPlayers));
+    let err = GameEngine::new(rules(MAX_PLAYERS + 1), &mut StdRng::seed_from_u64(7)).unwrap_err();
+    assert_eq!(err, RuleError::TooManyPlayers);
+}
+
+#[test]
+fn large_tables_get_a_second_deck()
+{
+    let single = new_deck(&Rules::default()).len();
+    assert_eq!(table_deck(&rules(4)).len(), single);
+
+    let deck = table_deck(&rules(MAX_PLAYERS));
+    assert_eq!(deck.len(), single * 2);
+    // Every card still has an id of its own
+    let mut ids: Vec<usize> = deck.iter().map(|card| card.id).collect();
+    ids.dedup();
+    assert_eq!(ids.len(), deck.len());
+
+    let engine = GameEngine::new(rules(MAX_PLAYERS), &mut StdRng::seed_from_u64(7)).unwrap();
+    assert_eq!(engine.deck().len(), single * 2 - MAX_PLAYERS * HAND_SIZE - 1);
+}
+
+#[test]
+fn card_must_match_rank_or_suit()
+{
+    let mut engine = table(rules(3));
+
+    assert_eq!(engine.apply(Action::Play { seat: 0, index: 0 }), Err(RuleError::CardDoesNotMatch));
+    assert_eq!(engine.apply(Action::Play { seat: 0, index: 5 }), Err(RuleError::NoSuchCard));
+
+    let events = engine.apply(Action::Play { seat: 0, index: 1 }).unwrap();
+    assert_eq!(events, vec![
+        Event::CardPlayed { seat: 0, card: card(Rank::Five, Suit::Red, 1) },
+        Event::TurnChanged { seat: 1 },
+    ]);
+    assert_eq!(engine.top_card().id, 1);
+    assert_eq!(engine.hand(0).len(), 1);
+}
+
+#[test]
+fn only_current_seat_can_act()
+{
+    let mut engine = table(rules(3));
+
+    assert_eq!(engine.apply(Action::Play { seat: 1, index: 1 }), Err(RuleError::NotYourTurn));
+    assert_eq!(engine.apply(Action::Draw { seat: 2 }), Err(RuleError::NotYourTurn));
+}
+
+#[test]
+fn draws_once_then_passes()
+{
+    let mut engine = table(rules(3));
+
+    assert_eq!(engine.apply(Action::Pass { seat: 0 }), Err(RuleError::MustDrawFirst));
+    let events = engine.apply(Action::Draw { seat: 0 }).unwrap();
+    assert_eq!(events, vec![Event::CardDrawn { seat: 0, card: card(Rank::Eight, Suit::Blue, 109) }]);
+    assert!(engine.has_drawn());
+    assert_eq!(engine.apply(Action::Draw { seat: 0 }), Err(RuleError::AlreadyDrawn));
+
+    assert_eq!(engine.apply(Action::Pass { seat: 0 }).unwrap(), vec![Event::TurnChanged { seat: 1 }]);
+    assert!(!engine.has_drawn());
+}
+
+#[test]
+fn drawing_from_an_empty_deck_fails()
+{
+    let rules = rules(2);
+    let hands = vec![vec![card(Rank::One, Suit::Green, 0)], vec![card(Rank::One, Suit::Blue, 1)]];
+    let mut engine = GameEngine::with_cards(rules, hands, vec![card(Rank::Two, Suit::Red, 2)], vec![]);
+
+    assert_eq!(engine.apply(Action::Draw { seat: 0 }), Err(RuleError::DeckEmpty));
+    // Without anything to draw a turn can be given up right away
+    assert!(!engine.can_draw());
+    engine.apply(Action::Pass { seat: 0 }).unwrap();
+    assert_eq!(engine.turn(), 1);
+}
+
+#[test]
+fn empty_deck_is_refilled_from_the_discard_pile()
+{
+    let rules = Rules { no_skip: true, ..rules(2) };
+    let hands = vec![vec![card(Rank::One, Suit::Green, 0)], vec![card(Rank::One, Suit::Blue, 1)]];
+    let discard_pile = vec![card(Rank::Five, Suit::Red, 2), card(Rank::Six, Suit::Red, 3), card(Rank::Two, Suit::Red, 4)];
+    let mut engine = GameEngine::with_cards(rules, hands, discard_pile, vec![card(Rank::Nine, Suit::Blue, 5)]);
+
+    engine.apply(Action::Draw { seat: 0 }).unwrap();
+    let events = engine.apply(Action::Draw { seat: 0 }).unwrap();
+    assert_eq!(events[0], Event::DeckReshuffled { count: 2 });
+    assert_eq!(engine.discard_pile(), [card(Rank::Two, Suit::Red, 4)]);
+    assert_eq!(engine.deck().len(), 1);
+    assert_eq!(engine.hand(0).len(), 3);
+}
+
+#[test]
+fn same_seed_deals_and_reshuffles_the_same()
+{
+    let rules = Rules { last_call: false, ..rules(3) };
+    let mut first = GameEngine::new(rules, &mut StdRng::seed_from_u64(42)).unwrap();
+    let mut second = GameEngine::new(rules, &mut StdRng::seed_from_u64(42)).unwrap();
+    assert_eq!(first.hand(0), second.hand(0));
+    assert_eq!(first.deck(), second.deck());
+
+    // Same moves on both tables draw the same cards, through reshuffles too. Every seat draws
+    // before it plays, so the deck runs out long before anybody wins
+    let mut reshuffled = false;
+    for _ in 0..500
+    {
+        let seat = first.turn();
+        let playable = first.hand(seat).iter().position(|card| first.can_play(card));
+        let action = match playable
+        {
+            _ if first.choosing_color() => Action::ChooseColor { seat, suit: Suit::Red },
+            _ if !first.has_drawn() && first.can_draw() => Action::Draw { seat },
+            Some(index) => Action::Play { seat, index },
+            None => Action::Pass { seat },
+        };
+        let events = first.apply(action).unwrap();
+        reshuffled |= events.iter().any(|event| matches!(event, Event::DeckReshuffled { .. }));
+        assert_eq!(second.apply(action).unwrap(), events);
+    }
+    assert!(reshuffled);
+}
+
+#[test]
+fn counter_clockwise_goes_up_the_seats()
+{
+    let mut engine = table(rules(3));
+
+    engine.apply(Action::Play { seat: 0, index: 1 }).unwrap();
+    assert_eq!(engine.turn(), 1);
+    engine.apply(Action::Play { seat: 1, index: 1 }).unwrap();
+    assert_eq!(engine.turn(), 2);
+    engine.apply(Action::Play { seat: 2, index: 1 }).unwrap();
+    assert_eq!(engine.turn(), 0);
+}
+
+#[test]
+fn clockwise_goes_down_the_seats()
+{
+    let mut engine = table(Rules { clockwise: true, ..rules(3) });
+
+    engine.apply(Action::Play { seat: 0, index: 1 }).unwrap();
+    assert_eq!(engine.turn(), 2);
+    engine.apply(Action::Play { seat: 2, index: 1 }).unwrap();
+    assert_eq!(engine.turn(), 1);
+}
+
+#[test]
+fn single_player_keeps_the_turn()
+{
+    let mut engine = table(rules(1));
+
+    engine.apply(Action::Play { seat: 0, index: 1 }).unwrap();
+    assert_eq!(engine.turn(), 0);
+    engine.apply(Action::Draw { seat: 0 }).unwrap();
+    engine.apply(Action::Pass { seat: 0 }).unwrap();
+    assert_eq!(engine.turn(), 0);
+}
+
+
+#[test]
+fn no_skip_allows_drawing_many_but_never_passing()
+{
+    let mut engine = table(Rules { no_skip: true, ..rules(3) });
+
+    engine.apply(Action::Draw { seat: 0 }).unwrap();
+    engine.apply(Action::Draw { seat: 0 }).unwrap();
+    assert_eq!(engine.hand(0).len(), 4);
+    assert_eq!(engine.apply(Action::Pass { seat: 0 }), Err(RuleError::PassNotAllowed));
+}
+
+#[test]
+fn turbo_does_not_change_the_rules_of_a_turn()
+{
+    let mut engine = table(Rules { turbo: true, ..rules(3) });
+
+    engine.apply(Action::Play { seat: 0, index: 1 }).unwrap();
+    assert_eq!(engine.turn(), 1);
+}
+
+#[test]
+fn turn_running_out_draws_a_card_and_passes()
+{
+    let mut engine = table(Rules { turbo: true, ..rules(3) });
+
+    let events = engine.apply(Action::TimeOut { seat: 0 }).unwrap();
+    assert_eq!(events, vec![
+        Event::TimedOut { seat: 0 },
+        Event::CardDrawn { seat: 0, card: card(Rank::Eight, Suit::Blue, 109) },
+        Event::TurnChanged { seat: 1 },
+    ]);
+
+    // No second card after a draw
+    engine.apply(Action::Draw { seat: 1 }).unwrap();
+    let events = engine.apply(Action::TimeOut { seat: 1 }).unwrap();
+    assert_eq!(events, vec![Event::TimedOut { seat: 1 }, Event::TurnChanged { seat: 2 }]);
+
+    assert_eq!(engine.apply(Action::TimeOut { seat: 0 }), Err(RuleError::NotYourTurn));
+    let mut engine = table(rules(3));
+    assert_eq!(engine.apply(Action::TimeOut { seat: 0 }), Err(RuleError::NotTurbo));
+}
+
+#[test]
+fn turn_starts_anew_when_a_seat_keeps_it()
+{
+    let mut engine = table_opening_with(Rules { turbo: true, ..rules(2) }, Rank::Skip);
+    assert_eq!(engine.turns(), 0);
+
+    engine.apply(Action::Play { seat: 0, index: 0 }).unwrap();
+    assert_eq!(engine.turn(), 0);
+    assert_eq!(engine.turns(), 1);
+}
+
+#[test]
+fn wild_card_keeps_the_old_color_when_time_runs_out()
+{
+    let mut engine = table_with(Rules { turbo: true, ..rules(3) }, |hands| hands[0][0] = card(Rank::Wild, Suit::Black, 50));
+    engine.apply(Action::Play { seat: 0, index: 0 }).unwrap();
+
+    let events = engine.apply(Action::TimeOut { seat: 0 }).unwrap();
+    assert_eq!(events, vec![
+        Event::TimedOut { seat: 0 },
+        Event::ColorChosen { seat: 0, suit: Suit::Red },
+        Event::TurnChanged { seat: 1 },
+    ]);
+    assert_eq!(engine.color(), Suit::Red);
+}
+
+#[test]
+fn skip_jumps_over_the_next_seat()
+{
+    let mut engine = table_opening_with(rules(4), Rank::Skip);
+
+    let events = engine.apply(Action::Play { seat: 0, index: 0 }).unwrap();
+    assert_eq!(events[1..], [Event::TurnSkipped { seat: 1 }, Event::TurnChanged { seat: 2 }]);
+    assert_eq!(engine.turn(), 2);
+}
+
+#[test]
+fn reverse_flips_the_direction()
+{
+    let mut engine = table_opening_with(rules(4), Rank::Reverse);
+
+    let events = engine.apply(Action::Play { seat: 0, index: 0 }).unwrap();
+    assert_eq!(events[1..], [Event::DirectionChanged { clockwise: true }, Event::TurnChanged { seat: 3 }]);
+    assert!(engine.clockwise());
+
+    // Direction stays flipped for the following turns
+    engine.apply(Action::Play { seat: 3, index: 1 }).unwrap();
+    assert_eq!(engine.turn(), 2);
+}
+
+#[test]
+fn reverse_with_two_players_acts_as_skip()
+{
+    let mut engine = table_opening_with(rules(2), Rank::Reverse);
+
+    engine.apply(Action::Play { seat: 0, index: 0 }).unwrap();
+    assert_eq!(engine.turn(), 0);
+}
+
+#[test]
+fn draw_two_gives_cards_and_skips_the_victim()
+{
+    let mut engine = table_opening_with(rules(3), Rank::Draw2);
+
+    let events = engine.apply(Action::Play { seat: 0, index: 0 }).unwrap();
+    assert_eq!(events[1..], [
+        Event::CardDrawn { seat: 1, card: card(Rank::Eight, Suit::Blue, 109) },
+        Event::CardDrawn { seat: 1, card: card(Rank::Eight, Suit::Blue, 108) },
+        Event::TurnSkipped { seat: 1 },
+        Event::TurnChanged { seat: 2 },
+    ]);
+    assert_eq!(engine.hand(1).len(), 4);
+    assert_eq!(engine.turn(), 2);
+}
+
+#[test]
+fn deck_has_four_of_each_wild_card()
+{
+    let deck = new_deck(&Rules::default());
+
+    assert_eq!(deck.iter().filter(|card| card.rank == Rank::Wild && card.suite == Suit::Black).count(), 4);
+    assert_eq!(deck.iter().filter(|card| card.rank == Rank::WildDraw4 && card.suite == Suit::Black).count(), 4);
+    assert_eq!(deck.iter().filter(|card| card.suite == Suit::Black).count(), 8);
+}
+
+#[test]
+fn never_starts_with_a_wild_card_on_the_pile()
+{
+    let mut deck = new_deck(&Rules::default());
+    // Put wild cards where the pile is flipped from
+    deck.sort_by_key(|card| card.suite == Suit::Black);
+
+    let engine = GameEngine::deal(rules(2), deck).unwrap();
+    assert_ne!(engine.top_card().suite, Suit::Black);
+    assert_eq!(engine.color(), engine.top_card().suite);
+}
+
+#[test]
+fn wild_waits_for_a_color_before_passing_the_turn()
+{
+    let mut engine = table_with(rules(3), |hands| hands[0][0] = card(Rank::Wild, Suit::Black, 50));
+
+    assert_eq!(engine.apply(Action::Play { seat: 0, index: 0 }).unwrap().len(), 1);
+    assert!(engine.choosing_color());
+    assert_eq!(engine.turn(), 0);
+    assert_eq!(engine.apply(Action::Draw { seat: 0 }), Err(RuleError::MustChooseColor));
+    assert_eq!(engine.apply(Action::ChooseColor { seat: 0, suit: Suit::Black }), Err(RuleError::NotAColor));
+
+    let events = engine.apply(Action::ChooseColor { seat: 0, suit: Suit::Green }).unwrap();
+    assert_eq!(events, vec![Event::ColorChosen { seat: 0, suit: Suit::Green }, Event::TurnChanged { seat: 1 }]);
+    assert_eq!(engine.color(), Suit::Green);
+
+    // Red Five no longer fits, Green Nine does
+    assert_eq!(engine.apply(Action::Play { seat: 1, index: 1 }), Err(RuleError::CardDoesNotMatch));
+    engine.apply(Action::Play { seat: 1, index: 0 }).unwrap();
+    assert_eq!(engine.apply(Action::ChooseColor { seat: 2, suit: Suit::Red }), Err(RuleError::NoColorToChoose));
+}
+
+#[test]
+fn wild_draw_four_gives_four_cards_and_skips()
+{
+    let mut engine = table_with(rules(3), |hands| hands[0][0] = card(Rank::WildDraw4, Suit::Black, 50));
+
+    engine.apply(Action::Play { seat: 0, index: 0 }).unwrap();
+    engine.apply(Action::ChooseColor { seat: 0, suit: Suit::Blue }).unwrap();
+
+    assert_eq!(engine.hand(1).len(), 6);
+    assert_eq!(engine.turn(), 2);
+    assert!(!engine.challenge_pending());
+}
+
+#[test]
+fn unchallenged_wild_draw_four_is_accepted_by_drawing()
+{
+    let rules = Rules { wild_draw4_challenge: true, ..rules(3) };
+    let mut engine = table_with(rules, |hands| hands[0][0] = card(Rank::WildDraw4, Suit::Black, 50));
+
+    engine.apply(Action::Play { seat: 0, index: 0 }).unwrap();
+    engine.apply(Action::ChooseColor { seat: 0, suit: Suit::Blue }).unwrap();
+    assert!(engine.challenge_pending());
+    assert_eq!(engine.turn(), 1);
+    assert_eq!(engine.apply(Action::Play { seat: 1, index: 1 }), Err(RuleError::MustAnswerChallenge));
+
+    engine.apply(Action::Draw { seat: 1 }).unwrap();
+    assert_eq!(engine.hand(1).len(), 6);
+    assert_eq!(engine.turn(), 2);
+}
+
+#[test]
+fn caught_bluff_makes_the_player_draw_four()
+{
+    // Main player still holds a Red Five, which matches the Red Two on the pile
+    let rules = Rules { wild_draw4_challenge: true, ..rules(3) };
+    let mut engine = table_with(rules, |hands| hands[0][0] = card(Rank::WildDraw4, Suit::Black, 50));
+
+    engine.apply(Action::Play { seat: 0, index: 0 }).unwrap();
+    engine.apply(Action::ChooseColor { seat: 0, suit: Suit::Blue }).unwrap();
+
+    let events = engine.apply(Action::Challenge { seat: 1 }).unwrap();
+    assert_eq!(events[0], Event::Challenged { seat: 1, bluffed: true });
+    assert_eq!(engine.hand(0).len(), 5);
+    assert_eq!(engine.hand(1).len(), 2);
+    // Challenger plays as usual
+    assert_eq!(engine.turn(), 1);
+}
+
+#[test]
+fn failed_challenge_costs_six_cards()
+{
+    let rules = Rules { wild_draw4_challenge: true, ..rules(3) };
+    let mut engine = table_with(rules, |hands| hands[0] = vec![card(Rank::WildDraw4, Suit::Black, 50), card(Rank::Nine, Suit::Green, 51)]);
+
+    engine.apply(Action::Play { seat: 0, index: 0 }).unwrap();
+    engine.apply(Action::ChooseColor { seat: 0, suit: Suit::Blue }).unwrap();
+
+    let events = engine.apply(Action::Challenge { seat: 1 }).unwrap();
+    assert_eq!(events[0], Event::Challenged { seat: 1, bluffed: false });
+    assert_eq!(engine.hand(1).len(), 8);
+    assert_eq!(engine.turn(), 2);
+}
+
+// Seats 0 and 1 hold a Draw2, seat 2 a Wild Draw 4, with stacking turned on
+fn stacking_table(stack_draw4: bool) -> GameEngine
+{
+    let rules = Rules { stackable_cards: true, stack_draw4, ..rules(3) };
+    table_with(rules, |hands| {
+        hands[0][0] = card(Rank::Draw2, Suit::Red, 50);
+        hands[1][0] = card(Rank::Draw2, Suit::Blue, 51);
+        hands[2][0] = card(Rank::WildDraw4, Suit::Black, 52);
+    })
+}
+
+#[test]
+fn stacked_draw_two_passes_the_penalty_on()
+{
+    let mut engine = stacking_table(false);
+
+    let events = engine.apply(Action::Play { seat: 0, index: 0 }).unwrap();
+    assert_eq!(events[1..], [Event::TurnChanged { seat: 1 }]);
+    assert_eq!(engine.penalty(), 2);
+
+    // Any Draw2 stacks, whatever its color
+    engine.apply(Action::Play { seat: 1, index: 0 }).unwrap();
+    assert_eq!(engine.penalty(), 4);
+    assert_eq!(engine.turn(), 2);
+}
+
+#[test]
+fn only_draw_cards_can_be_stacked()
+{
+    let mut engine = stacking_table(false);
+    engine.apply(Action::Play { seat: 0, index: 0 }).unwrap();
+
+    // Blue Draw2 went away, Red Five is left
+    assert_eq!(engine.apply(Action::Play { seat: 1, index: 1 }), Err(RuleError::MustStackOrDraw));
+    engine.apply(Action::Play { seat: 1, index: 0 }).unwrap();
+    // Wild Draw 4 on a Draw2 is off by default
+    assert_eq!(engine.apply(Action::Play { seat: 2, index: 0 }), Err(RuleError::MustStackOrDraw));
+    assert_eq!(engine.apply(Action::Pass { seat: 2 }), Err(RuleError::MustDrawFirst));
+}
+
+#[test]
+fn seat_unable_to_stack_draws_the_whole_penalty()
+{
+    let mut engine = stacking_table(false);
+    engine.apply(Action::Play { seat: 0, index: 0 }).unwrap();
+    engine.apply(Action::Play { seat: 1, index: 0 }).unwrap();
+
+    let events = engine.apply(Action::Draw { seat: 2 }).unwrap();
+    assert_eq!(events[4..], [Event::TurnSkipped { seat: 2 }, Event::TurnChanged { seat: 0 }]);
+    assert_eq!(engine.hand(2).len(), 6);
+    assert_eq!(engine.penalty(), 0);
+
+    // Game goes back to normal matching
+    engine.apply(Action::Draw { seat: 0 }).unwrap();
+    assert!(engine.has_drawn());
+}
+
+#[test]
+fn wild_draw_four_can_be_stacked_on_draw_two()
+{
+    let mut engine = stacking_table(true);
+    engine.apply(Action::Play { seat: 0, index: 0 }).unwrap();
+    engine.apply(Action::Play { seat: 1, index: 0 }).unwrap();
+
+    engine.apply(Action::Play { seat: 2, index: 0 }).unwrap();
+    engine.apply(Action::ChooseColor { seat: 2, suit: Suit::Green }).unwrap();
+    assert_eq!(engine.penalty(), 8);
+    assert_eq!(engine.turn(), 0);
+
+    engine.apply(Action::Draw { seat: 0 }).unwrap();
+    assert_eq!(engine.hand(0).len(), 9);
+    assert_eq!(engine.turn(), 1);
+}
+
+#[test]
+fn progressive_draw_two_goes_on_wild_draw_four_of_chosen_color()
+{
+    let rules = Rules { progressive: true, ..rules(3) };
+    let mut engine = table_with(rules, |hands| {
+        hands[0][0] = card(Rank::WildDraw4, Suit::Black, 50);
+        hands[1] = vec![card(Rank::Draw2, Suit::Red, 51), card(Rank::Draw2, Suit::Blue, 52), card(Rank::Five, Suit::Red, 53)];
+    });
+    engine.apply(Action::Play { seat: 0, index: 0 }).unwrap();
+    engine.apply(Action::ChooseColor { seat: 0, suit: Suit::Blue }).unwrap();
+    assert_eq!(engine.penalty(), 4);
+
+    assert_eq!(engine.apply(Action::Play { seat: 1, index: 0 }), Err(RuleError::MustStackOrDraw));
+    engine.apply(Action::Play { seat: 1, index: 1 }).unwrap();
+    assert_eq!(engine.penalty(), 6);
+    assert_eq!(engine.turn(), 2);
+}
+
+#[test]
+fn stacked_penalty_stops_at_a_cap()
+{
+    let rules = Rules { stackable_cards: true, penalty_cap: 5, ..rules(3) };
+    let mut engine = table_with(rules, |hands| {
+        hands[0][0] = card(Rank::Draw2, Suit::Red, 50);
+        hands[1][0] = card(Rank::Draw2, Suit::Blue, 51);
+        hands[2][0] = card(Rank::Draw2, Suit::Blue, 52);
+    });
+    engine.apply(Action::Play { seat: 0, index: 0 }).unwrap();
+    engine.apply(Action::Play { seat: 1, index: 0 }).unwrap();
+    engine.apply(Action::Play { seat: 2, index: 0 }).unwrap();
+    assert_eq!(engine.penalty(), 5);
+
+    engine.apply(Action::Draw { seat: 0 }).unwrap();
+    assert_eq!(engine.hand(0).len(), 6);
+}
+
+#[test]
+fn penalty_cap_has_to_fit_a_wild_draw_4()
+{
+    assert_eq!(Rules { penalty_cap: 3, ..rules(3) }.validate(), Err(RuleError::PenaltyCapTooLow));
+    assert_eq!(Rules { penalty_cap: MIN_PENALTY_CAP, ..rules(3) }.validate(), Ok(()));
+    assert_eq!(Rules { penalty_cap: 0, ..rules(3) }.validate(), Ok(()));
+}
+
+// Seat 0 opens a stack with a Red Draw2, seat 1 holds a Red Skip and a Red Reverse
+fn deflecting_table() -> GameEngine
+{
+    let rules = Rules { stackable_cards: true, deflect_penalty: true, ..rules(3) };
+    table_with(rules, |hands| {
+        hands[0][0] = card(Rank::Draw2, Suit::Red, 50);
+        hands[1] = vec![card(Rank::Skip, Suit::Red, 51), card(Rank::Reverse, Suit::Red, 52), card(Rank::Skip, Suit::Blue, 53)];
+    })
+}
+
+#[test]
+fn skip_passes_a_penalty_on_to_the_next_seat()
+{
+    let mut engine = deflecting_table();
+    engine.apply(Action::Play { seat: 0, index: 0 }).unwrap();
+
+    // Only a Skip of the current color deflects
+    assert_eq!(engine.apply(Action::Play { seat: 1, index: 2 }), Err(RuleError::MustStackOrDraw));
+    engine.apply(Action::Play { seat: 1, index: 0 }).unwrap();
+    assert_eq!(engine.turn(), 2);
+    assert_eq!(engine.penalty(), 2);
+
+    engine.apply(Action::Draw { seat: 2 }).unwrap();
+    assert_eq!(engine.hand(2).len(), 4);
+}
+
+#[test]
+fn reverse_sends_a_penalty_back()
+{
+    let mut engine = deflecting_table();
+    engine.apply(Action::Play { seat: 0, index: 0 }).unwrap();
+
+    engine.apply(Action::Play { seat: 1, index: 1 }).unwrap();
+    assert_eq!(engine.turn(), 0);
+    assert_eq!(engine.penalty(), 2);
+}
+
+#[test]
+fn penalties_are_not_deflected_by_default()
+{
+    let mut engine = table_with(Rules { stackable_cards: true, ..rules(3) }, |hands| {
+        hands[0][0] = card(Rank::Draw2, Suit::Red, 50);
+        hands[1][0] = card(Rank::Skip, Suit::Red, 51);
+    });
+    engine.apply(Action::Play { seat: 0, index: 0 }).unwrap();
+    assert_eq!(engine.apply(Action::Play { seat: 1, index: 0 }), Err(RuleError::MustStackOrDraw));
+}
+
+#[test]
+fn playing_the_last_card_wins_the_round()
+{
+    // Only the Red Five is left
+    let mut engine = table_with(rules(3), |hands| { hands[0].remove(0); });
+    assert_eq!(engine.winner(), None);
+
+    let events = engine.apply(Action::Play { seat: 0, index: 0 }).unwrap();
+    assert_eq!(events.last(), Some(&Event::RoundWon { seat: 0 }));
+    assert_eq!(engine.winner(), Some(0));
+    assert_eq!(engine.apply(Action::Draw { seat: 0 }), Err(RuleError::RoundOver));
+    assert_eq!(engine.apply(Action::Draw { seat: 1 }), Err(RuleError::RoundOver));
+}
+
+#[test]
+fn last_wild_card_needs_no_color()
+{
+    let mut engine = table_with(rules(3), |hands| hands[0] = vec![card(Rank::WildDraw4, Suit::Black, 50)]);
+
+    engine.apply(Action::Play { seat: 0, index: 0 }).unwrap();
+    assert!(!engine.choosing_color());
+    assert_eq!(engine.hand(1).len(), 2);
+    assert_eq!(engine.winner(), Some(0));
+}
+
+#[test]
+fn cards_are_worth_classic_points()
+{
+    assert_eq!(card_points(&card(Rank::Zero, Suit::Red, 0)), 0);
+    assert_eq!(card_points(&card(Rank::Seven, Suit::Blue, 0)), 7);
+    assert_eq!(card_points(&card(Rank::Reverse, Suit::Green, 0)), 20);
+    assert_eq!(card_points(&card(Rank::Draw2, Suit::Yellow, 0)), 20);
+    assert_eq!(card_points(&card(Rank::WildDraw4, Suit::Black, 0)), 50);
+}
+
+#[test]
+fn winner_scores_cards_left_in_other_hands()
+{
+    let mut engine = table_with(rules(3), |hands| {
+        hands[0].remove(0);
+        hands[2][0] = card(Rank::Wild, Suit::Black, 50);
+    });
+    let mut scoreboard = Scoreboard::new(3);
+    assert_eq!(scoreboard.record(&engine), None);
+
+    engine.apply(Action::Play { seat: 0, index: 0 }).unwrap();
+    // Green Nine and Red Five, then a Wild and Red Five
+    assert_eq!(scoreboard.record(&engine), Some((0, 69)));
+    assert_eq!(scoreboard.scores(), [69, 0, 0]);
+    assert_eq!(scoreboard.rounds(), 1);
+}
+
+#[test]
+fn match_ends_once_a_target_is_reached()
+{
+    let mut engine = table_with(rules(2), |hands| { hands[0].remove(0); });
+    engine.apply(Action::Play { seat: 0, index: 0 }).unwrap();
+
+    let mut scoreboard = Scoreboard::new(2);
+    scoreboard.record(&engine);
+    assert_eq!(scoreboard.match_winner(15), None);
+    scoreboard.record(&engine);
+    assert_eq!(scoreboard.match_winner(15), Some(0));
+}
+
+#[test]
+fn default_deck_has_a_single_zero_per_color()
+{
+    let deck = new_deck(&Rules::default());
+
+    assert_eq!(deck.len(), 108);
+    assert_eq!(deck.iter().filter(|card| card.rank == Rank::Zero).count(), 4);
+    assert_eq!(deck.iter().filter(|card| card.rank == Rank::Seven).count(), 8);
+}
+
+#[test]
+fn deck_follows_composition_rules()
+{
+    let rules = Rules { rank_copies: 1, action_cards: false, wild_cards: false, ..rules(2) };
+    let deck = new_deck(&rules);
+
+    assert_eq!(deck.len(), 40);
+    assert!(deck.iter().all(|card| card.rank as usize <= Rank::Nine as usize && card.suite != Suit::Black));
+    assert_eq!(table_deck(&Rules { deck_multiplier: 3, ..rules }).len(), 120);
+}
+
+#[test]
+fn deals_hands_of_a_chosen_size()
+{
+    let engine = GameEngine::new(Rules { hand_size: 3, ..rules(4) }, &mut StdRng::seed_from_u64(7)).unwrap();
+    assert_eq!(engine.hand(2).len(), 3);
+
+    let err = GameEngine::new(Rules { hand_size: 0, ..rules(4) }, &mut StdRng::seed_from_u64(7)).unwrap_err();
+    assert_eq!(err, RuleError::EmptyHands);
+}
+
+#[test]
+fn turns_have_to_last_some_time()
+{
+    assert_eq!(Rules { turn_time: 0, ..rules(4) }.validate(), Err(RuleError::NoTurnTime));
+    assert_eq!(Rules { turn_time: 1, ..rules(4) }.validate(), Ok(()));
+}
+
+#[test]
+fn seven_swaps_hands_with_a_chosen_seat()
+{
+    let rules = Rules { seven_o: true, ..rules(3) };
+    let mut engine = table_with(rules, |hands| hands[0][0] = card(Rank::Seven, Suit::Red, 50));
+    let theirs = engine.hand(2).to_vec();
+
+    engine.apply(Action::Play { seat: 0, index: 0 }).unwrap();
+    assert!(engine.choosing_swap());
+    assert_eq!(engine.turn(), 0);
+    assert_eq!(engine.apply(Action::Draw { seat: 0 }), Err(RuleError::MustPickSwap));
+    assert_eq!(engine.apply(Action::SwapHands { seat: 0, with: 0 }), Err(RuleError::NoSuchSeat));
+
+    let events = engine.apply(Action::SwapHands { seat: 0, with: 2 }).unwrap();
+    assert_eq!(events, vec![Event::HandsSwapped { seat: 0, with: 2 }, Event::TurnChanged { seat: 1 }]);
+    assert_eq!(engine.hand(0), theirs);
+    assert_eq!(engine.hand(2), [card(Rank::Five, Suit::Red, 1)]);
+}
+
+#[test]
+fn zero_passes_every_hand_on()
+{
+    let rules = Rules { seven_o: true, ..rules(3) };
+    let mut engine = table_with(rules, |hands| hands[0][0] = card(Rank::Zero, Suit::Red, 50));
+    let (first, second) = (engine.hand(1).to_vec(), engine.hand(2).to_vec());
+
+    let events = engine.apply(Action::Play { seat: 0, index: 0 }).unwrap();
+    assert_eq!(events[1], Event::HandsRotated { clockwise: false });
+    assert_eq!(engine.hand(1), [card(Rank::Five, Suit::Red, 1)]);
+    assert_eq!(engine.hand(2), first);
+    assert_eq!(engine.hand(0), second);
+    assert_eq!(engine.turn(), 1);
+}
+
+#[test]
+fn seven_and_zero_are_plain_cards_without_the_rule()
+{
+    let mut engine = table_with(rules(3), |hands| hands[0][0] = card(Rank::Seven, Suit::Red, 50));
+
+    engine.apply(Action::Play { seat: 0, index: 0 }).unwrap();
+    assert!(!engine.choosing_swap());
+    assert_eq!(engine.turn(), 1);
+}
+
+#[test]
+fn identical_card_jumps_in_out_of_turn()
+{
+    let rules = Rules { jump_in: true, ..rules(4) };
+    let mut engine = table_with(rules, |hands| hands[2][0] = card(Rank::Two, Suit::Red, 50));
+
+    let events = engine.apply(Action::Play { seat: 2, index: 0 }).unwrap();
+    assert_eq!(events, vec![
+        Event::JumpedIn { seat: 2 },
+        Event::CardPlayed { seat: 2, card: card(Rank::Two, Suit::Red, 50) },
+        Event::TurnChanged { seat: 3 },
+    ]);
+    assert_eq!(engine.turn(), 3);
+}
+
+#[test]
+fn only_identical_cards_jump_in()
+{
+    let mut engine = table_with(Rules { jump_in: true, ..rules(4) }, |hands| hands[2][0] = card(Rank::Two, Suit::Blue, 50));
+
+    // Same rank in another color, and a matching color of another rank
+    assert_eq!(engine.apply(Action::Play { seat: 2, index: 0 }), Err(RuleError::NotYourTurn));
+    assert_eq!(engine.apply(Action::Play { seat: 2, index: 1 }), Err(RuleError::NotYourTurn));
+    assert_eq!(engine.turn(), 0);
+
+    // Nobody jumps in without the rule
+    let mut engine = table_with(rules(4), |hands| hands[2][0] = card(Rank::Two, Suit::Red, 50));
+    assert_eq!(engine.apply(Action::Play { seat: 2, index: 0 }), Err(RuleError::NotYourTurn));
+}
+
+#[test]
+fn draws_until_a_card_fits()
+{
+    let rules = Rules { draw_until_playable: true, ..rules(2) };
+    let hands = vec![vec![card(Rank::One, Suit::Green, 0)], vec![card(Rank::One, Suit::Blue, 1)]];
+    let deck = vec![card(Rank::Four, Suit::Red, 5), card(Rank::Six, Suit::Blue, 4), card(Rank::Eight, Suit::Green, 3)];
+    let mut engine = GameEngine::with_cards(rules, hands, vec![card(Rank::Two, Suit::Red, 2)], deck);
+
+    let events = engine.apply(Action::Draw { seat: 0 }).unwrap();
+    assert_eq!(events, vec![
+        Event::CardDrawn { seat: 0, card: card(Rank::Eight, Suit::Green, 3) },
+        Event::CardDrawn { seat: 0, card: card(Rank::Six, Suit::Blue, 4) },
+        Event::CardDrawn { seat: 0, card: card(Rank::Four, Suit::Red, 5) },
+    ]);
+    assert_eq!(engine.turn(), 0);
+    assert_eq!(engine.apply(Action::Draw { seat: 0 }), Err(RuleError::AlreadyDrawn));
+
+    // Drawing stops with an empty deck, after which a turn can be passed
+    let mut engine = table(rules);
+    let events = engine.apply(Action::Draw { seat: 0 }).unwrap();
+    assert_eq!(events.len(), 10);
+    engine.apply(Action::Pass { seat: 0 }).unwrap();
+}
+
+#[test]
+fn drawn_card_that_fits_must_be_played()
+{
+    let rules = Rules { after_draw: AfterDraw::MustPlayDrawn, ..rules(2) };
+    let hands = vec![vec![card(Rank::One, Suit::Green, 0)], vec![card(Rank::One, Suit::Blue, 1)]];
+    let mut engine = GameEngine::with_cards(rules, hands.clone(), vec![card(Rank::Two, Suit::Red, 2)], vec![card(Rank::Two, Suit::Blue, 3)]);
+
+    engine.apply(Action::Draw { seat: 0 }).unwrap();
+    assert_eq!(engine.apply(Action::Pass { seat: 0 }), Err(RuleError::MustPlayDrawn));
+    engine.apply(Action::Play { seat: 0, index: 1 }).unwrap();
+
+    // Turn passes as usual after drawing a card that doesn't fit
+    let mut engine = GameEngine::with_cards(rules, hands, vec![card(Rank::Two, Suit::Red, 2)], vec![card(Rank::Six, Suit::Blue, 3)]);
+    engine.apply(Action::Draw { seat: 0 }).unwrap();
+    engine.apply(Action::Pass { seat: 0 }).unwrap();
+}
+
+// Main player holds a Red Five that fits the Red Two on the pile, a Blue Two is drawn
+fn drawing_table(after_draw: AfterDraw) -> GameEngine
+{
+    let rules = Rules { after_draw, ..rules(2) };
+    let hands = vec![vec![card(Rank::Five, Suit::Red, 0)], vec![card(Rank::One, Suit::Blue, 1)]];
+    GameEngine::with_cards(rules, hands, vec![card(Rank::Two, Suit::Red, 2)], vec![card(Rank::Two, Suit::Blue, 3)])
+}
+
+#[test]
+fn any_card_can_be_played_after_drawing_by_default()
+{
+    let mut engine = drawing_table(AfterDraw::AnyCard);
+    engine.apply(Action::Draw { seat: 0 }).unwrap();
+    assert_eq!(engine.drawn(), Some(&card(Rank::Two, Suit::Blue, 3)));
+    engine.apply(Action::Play { seat: 0, index: 0 }).unwrap();
+}
+
+#[test]
+fn only_the_drawn_card_can_be_played_after_drawing()
+{
+    let mut engine = drawing_table(AfterDraw::OnlyDrawn);
+    engine.apply(Action::Draw { seat: 0 }).unwrap();
+    assert!(engine.only_drawn_playable());
+    assert_eq!(engine.apply(Action::Play { seat: 0, index: 0 }), Err(RuleError::OnlyDrawnCard));
+    // Unlike with MustPlayDrawn, a drawn card that fits can be kept
+    engine.apply(Action::Pass { seat: 0 }).unwrap();
+    assert_eq!(engine.drawn(), None);
+
+    let mut engine = drawing_table(AfterDraw::OnlyDrawn);
+    engine.apply(Action::Draw { seat: 0 }).unwrap();
+    engine.apply(Action::Play { seat: 0, index: 1 }).unwrap();
+}
+
+#[test]
+fn drawn_card_that_must_be_played_is_the_only_one_allowed()
+{
+    let mut engine = drawing_table(AfterDraw::MustPlayDrawn);
+    engine.apply(Action::Draw { seat: 0 }).unwrap();
+    assert_eq!(engine.apply(Action::Play { seat: 0, index: 0 }), Err(RuleError::OnlyDrawnCard));
+    assert_eq!(engine.apply(Action::Pass { seat: 0 }), Err(RuleError::MustPlayDrawn));
+    engine.apply(Action::Play { seat: 0, index: 1 }).unwrap();
+}
+
+// DOS mode table with two Fives in the centre row and Eights left in the deck
+fn dos_table(hand: Vec<Card>) -> GameEngine
+{
+    let hands = vec![
+        hand,
+        vec![card(Rank::One, Suit::Green, 4), card(Rank::One, Suit::Blue, 5), card(Rank::Eight, Suit::Yellow, 6)],
+        vec![card(Rank::Four, Suit::Yellow, 7), card(Rank::Six, Suit::Yellow, 8), card(Rank::Seven, Suit::Green, 9)],
+    ];
+    let mut deck: Vec<Card> = (0..8).map(|i| card(Rank::Eight, Suit::Blue, 100 + i)).collect();
+    deck.extend([card(Rank::Five, Suit::Red, 108), card(Rank::Five, Suit::Blue, 109)]);
+    GameEngine::with_cards(Rules { dos: true, ..rules(3) }, hands, vec![card(Rank::Two, Suit::Yellow, 99)], deck)
+}
+
+#[test]
+fn dos_mode_deals_a_centre_row_of_number_cards()
+{
+    let engine = dos_table(vec![card(Rank::Nine, Suit::Green, 0)]);
+    assert_eq!(engine.centre_row(), [card(Rank::Five, Suit::Blue, 109), card(Rank::Five, Suit::Red, 108)]);
+    assert_eq!(engine.deck().len(), 8);
+
+    let engine = GameEngine::new(Rules { dos: true, ..rules(4) }, &mut StdRng::seed_from_u64(7)).unwrap();
+    assert_eq!(engine.centre_row().len(), CENTRE_ROW_SIZE);
+    let deck = new_deck(&Rules { dos: true, ..Rules::default() });
+    assert!(deck.iter().all(|card| card_number(card).is_some() || card.rank == Rank::Wild));
+}
+
+#[test]
+fn color_match_owes_a_card_to_the_centre_row()
+{
+    let hand = vec![card(Rank::Nine, Suit::Green, 0), card(Rank::Three, Suit::Red, 1), card(Rank::Five, Suit::Blue, 2), card(Rank::Six, Suit::Red, 3)];
+    let mut engine = dos_table(hand);
+
+    let events = engine.apply(Action::Match { seat: 0, row: 0, first: 2, second: None }).unwrap();
+    assert_eq!(events, vec![Event::Matched {
+        seat: 0,
+        target: card(Rank::Five, Suit::Blue, 109),
+        first: card(Rank::Five, Suit::Blue, 2),
+        second: None,
+        color_match: true,
+    }]);
+    assert_eq!(engine.owed_to_row(), 1);
+    assert_eq!(engine.apply(Action::Pass { seat: 0 }), Err(RuleError::MustAddToRow));
+
+    let events = engine.apply(Action::AddToRow { seat: 0, index: 0 }).unwrap();
+    assert_eq!(events, vec![Event::AddedToRow { seat: 0, card: card(Rank::Nine, Suit::Green, 0) }]);
+    assert_eq!(engine.apply(Action::AddToRow { seat: 0, index: 0 }), Err(RuleError::NothingToAdd));
+
+    // Centre row already has two cards, so nothing is added to it
+    assert_eq!(engine.apply(Action::Pass { seat: 0 }).unwrap(), vec![Event::TurnChanged { seat: 1 }]);
+    assert_eq!(engine.centre_row(), [card(Rank::Five, Suit::Red, 108), card(Rank::Nine, Suit::Green, 0)]);
+}
+
+#[test]
+fn double_color_match_makes_everybody_else_draw()
+{
+    let hand = vec![card(Rank::Three, Suit::Red, 0), card(Rank::Nine, Suit::Green, 1), card(Rank::Two, Suit::Red, 2), card(Rank::Six, Suit::Red, 3)];
+    let mut engine = dos_table(hand);
+
+    let events = engine.apply(Action::Match { seat: 0, row: 1, first: 0, second: Some(2) }).unwrap();
+    assert_eq!(events, vec![
+        Event::Matched {
+            seat: 0,
+            target: card(Rank::Five, Suit::Red, 108),
+            first: card(Rank::Three, Suit::Red, 0),
+            second: Some(card(Rank::Two, Suit::Red, 2)),
+            color_match: true,
+        },
+        Event::CardDrawn { seat: 1, card: card(Rank::Eight, Suit::Blue, 107) },
+        Event::CardDrawn { seat: 2, card: card(Rank::Eight, Suit::Blue, 106) },
+    ]);
+    assert_eq!(engine.hand(0), [card(Rank::Nine, Suit::Green, 1), card(Rank::Six, Suit::Red, 3)]);
+    assert_eq!(engine.top_card(), &card(Rank::Two, Suit::Red, 2));
+    assert_eq!(engine.apply(Action::Draw { seat: 0 }), Err(RuleError::AlreadyMatched));
+
+    engine.apply(Action::AddToRow { seat: 0, index: 1 }).unwrap();
+    let events = engine.apply(Action::Pass { seat: 0 }).unwrap();
+    assert_eq!(events, vec![Event::TurnChanged { seat: 1 }]);
+}
+
+#[test]
+fn matched_numbers_must_add_up()
+{
+    let hand = vec![card(Rank::Nine, Suit::Green, 0), card(Rank::Four, Suit::Green, 1), card(Rank::Two, Suit::Yellow, 2)];
+    let mut engine = dos_table(hand);
+
+    assert_eq!(engine.apply(Action::Match { seat: 0, row: 0, first: 0, second: None }), Err(RuleError::NoMatch));
+    assert_eq!(engine.apply(Action::Match { seat: 0, row: 0, first: 1, second: Some(2) }), Err(RuleError::NoMatch));
+    assert_eq!(engine.apply(Action::Match { seat: 0, row: 0, first: 1, second: Some(1) }), Err(RuleError::NoSuchCard));
+    assert_eq!(engine.apply(Action::Match { seat: 0, row: 2, first: 1, second: None }), Err(RuleError::NoSuchRowCard));
+    assert_eq!(engine.apply(Action::Play { seat: 0, index: 0 }), Err(RuleError::MatchInDosMode));
+    assert!(engine.matches(0).is_empty());
+
+    // Plain Uno has no centre row
+    let mut engine = table(rules(2));
+    assert_eq!(engine.apply(Action::AddToRow { seat: 0, index: 0 }), Err(RuleError::NotDosMode));
+}
+
+#[test]
+fn turn_without_a_match_ends_with_a_card_on_the_row()
+{
+    let mut engine = dos_table(vec![card(Rank::Nine, Suit::Green, 0), card(Rank::Seven, Suit::Green, 1)]);
+
+    assert_eq!(engine.apply(Action::Pass { seat: 0 }), Err(RuleError::MustDrawFirst));
+    assert_eq!(engine.apply(Action::AddToRow { seat: 0, index: 0 }), Err(RuleError::MustDrawFirst));
+    engine.apply(Action::Draw { seat: 0 }).unwrap();
+    assert_eq!(engine.apply(Action::Pass { seat: 0 }), Err(RuleError::MustAddToRow));
+
+    let events = engine.apply(Action::AddToRow { seat: 0, index: 0 }).unwrap();
+    assert_eq!(events, vec![
+        Event::AddedToRow { seat: 0, card: card(Rank::Nine, Suit::Green, 0) },
+        Event::TurnChanged { seat: 1 },
+    ]);
+    assert_eq!(engine.centre_row().len(), 3);
+}
+
+#[test]
+fn wild_stands_for_any_number()
+{
+    let five = card(Rank::Five, Suit::Red, 0);
+    let wild = card(Rank::Wild, Suit::Black, 1);
+
+    assert!(dos_match(&five, &[wild]));
+    assert!(dos_match(&five, &[wild, card(Rank::Three, Suit::Blue, 2)]));
+    assert!(!dos_match(&five, &[wild, card(Rank::Six, Suit::Blue, 2)]));
+    assert!(dos_match(&wild, &[card(Rank::Nine, Suit::Blue, 2)]));
+    assert!(!dos_match(&five, &[card(Rank::Skip, Suit::Red, 2)]));
+}
+
+#[test]
+fn last_card_has_to_be_called()
+{
+    let mut engine = table(rules(3));
+
+    engine.apply(Action::Play { seat: 0, index: 1 }).unwrap();
+    assert_eq!(engine.uncalled(), Some(0));
+    assert_eq!(engine.apply(Action::Call { seat: 1 }), Err(RuleError::NothingToCall));
+    assert_eq!(engine.apply(Action::Call { seat: 0 }).unwrap(), vec![Event::Called { seat: 0 }]);
+    assert_eq!(engine.uncalled(), None);
+    assert_eq!(engine.apply(Action::Catch { seat: 1, target: 0 }), Err(RuleError::NothingToCatch));
+}
+
+#[test]
+fn missed_call_costs_two_cards()
+{
+    let mut engine = table(rules(3));
+    engine.apply(Action::Play { seat: 0, index: 1 }).unwrap();
+
+    assert_eq!(engine.apply(Action::Catch { seat: 0, target: 0 }), Err(RuleError::NothingToCatch));
+    let events = engine.apply(Action::Catch { seat: 2, target: 0 }).unwrap();
+    assert_eq!(events, vec![
+        Event::Caught { seat: 2, target: 0 },
+        Event::CardDrawn { seat: 0, card: card(Rank::Eight, Suit::Blue, 109) },
+        Event::CardDrawn { seat: 0, card: card(Rank::Eight, Suit::Blue, 108) },
+    ]);
+    assert_eq!(engine.hand(0).len(), 3);
+    // Catching doesn't take a turn
+    assert_eq!(engine.turn(), 1);
+}
+
+#[test]
+fn missed_call_is_safe_once_the_next_seat_acts()
+{
+    let mut engine = table(rules(3));
+    engine.apply(Action::Play { seat: 0, index: 1 }).unwrap();
+    engine.apply(Action::Draw { seat: 1 }).unwrap();
+
+    assert_eq!(engine.uncalled(), None);
+    assert_eq!(engine.apply(Action::Catch { seat: 2, target: 0 }), Err(RuleError::NothingToCatch));
+
+    // Nothing has to be called without the rule, and DOS is called at two cards
+    let mut engine = table(Rules { last_call: false, ..rules(3) });
+    engine.apply(Action::Play { seat: 0, index: 1 }).unwrap();
+    assert_eq!(engine.uncalled(), None);
+
+    let hand = vec![card(Rank::Nine, Suit::Green, 0), card(Rank::Seven, Suit::Green, 1), card(Rank::Five, Suit::Red, 2)];
+    let mut engine = dos_table(hand);
+    engine.apply(Action::Match { seat: 0, row: 1, first: 2, second: None }).unwrap();
+    assert_eq!(engine.uncalled(), Some(0));
+}
+
+#[test]
+fn determinized_table_keeps_what_a_seat_can_see()
+{
+    let engine = table(rules(3));
+    let imagined = engine.determinize(0, &mut StdRng::seed_from_u64(5));
+
+    assert_eq!(imagined.hand(0), engine.hand(0));
+    assert_eq!(imagined.top_card(), engine.top_card());
+    assert_eq!(imagined.turn(), engine.turn());
+    for seat in 1..3
+    {
+        assert_eq!(imagined.hand(seat).len(), engine.hand(seat).len());
+    }
+    assert_eq!(imagined.deck().len(), engine.deck().len());
+
+    // Hidden cards are the same ones, only dealt differently
+    let hidden = |table: &GameEngine| {
+        let mut ids: Vec<usize> = (1..3).flat_map(|seat| table.hand(seat)).chain(table.deck()).map(|card| card.id).collect();
+        ids.sort();
+        ids
+    };
+    assert_eq!(hidden(&imagined), hidden(&engine));
+}
+
+#[test]
+fn determinized_table_tells_a_bluff_by_the_hand_dealt_again()
+{
+    // Main player bluffs with a Red Five left, which could as well be any hidden card
+    let rules = Rules { wild_draw4_challenge: true, ..rules(3) };
+    let mut engine = table_with(rules, |hands| hands[0][0] = card(Rank::WildDraw4, Suit::Black, 50));
+    engine.apply(Action::Play { seat: 0, index: 0 }).unwrap();
+    engine.apply(Action::ChooseColor { seat: 0, suit: Suit::Blue }).unwrap();
+
+    let mut honest = 0;
+    for seed in 0..20
+    {
+        let mut imagined = engine.determinize(1, &mut StdRng::seed_from_u64(seed));
+        let holds_red = imagined.hand(0).iter().any(|held| held.suite == Suit::Red);
+        let events = imagined.apply(Action::Challenge { seat: 1 }).unwrap();
+        assert_eq!(events[0], Event::Challenged { seat: 1, bluffed: holds_red });
+        if !holds_red { honest += 1; }
+    }
+    assert!(honest > 0);
+}
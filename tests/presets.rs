@@ -0,0 +1,56 @@
+use dos_game::engine::{RuleError, Rules};
+use dos_game::presets::*;
+
+#[test]
+fn built_in_presets_survive_a_round_trip_through_ron()
+{
+    let presets = RulePresets::default();
+    let text = presets.to_ron().unwrap();
+    assert_eq!(RulePresets::from_ron(&text).unwrap(), presets);
+}
+
+#[test]
+fn missing_rules_in_a_file_keep_their_defaults()
+{
+    let presets = RulePresets::from_ron(r#"(presets: [(name: "Duel", rules: (num_players: 2, jump_in: true))])"#).unwrap();
+    let duel = &presets.presets()[0];
+    assert_eq!(duel.name, "Duel");
+    assert_eq!(duel.rules, Rules { num_players: 2, jump_in: true, ..Rules::default() });
+}
+
+#[test]
+fn malformed_file_is_an_error()
+{
+    assert!(matches!(RulePresets::from_ron("(presets: [(name: 3)])"), Err(PresetError::Parse(_))));
+}
+
+#[test]
+fn custom_preset_is_replaced_when_saved_again()
+{
+    let mut presets = RulePresets::default();
+    let count = presets.presets().len();
+    presets.save_custom(Rules { num_players: 3, ..Rules::default() });
+    presets.save_custom(Rules { num_players: 8, ..Rules::default() });
+    assert_eq!(presets.presets().len(), count + 1);
+    assert_eq!(presets.find(&Rules { num_players: 8, ..Rules::default() }).unwrap().name, CUSTOM_PRESET);
+}
+
+#[test]
+fn summary_names_a_matching_preset_and_its_variants()
+{
+    let presets = RulePresets::default();
+    let party = presets.presets().iter().find(|preset| preset.name == "Party").unwrap().rules;
+    assert_eq!(presets.summary(&party), "Party: 6 players, to 300 points, stacking, draw to match, 7-0, jump-in");
+    assert_eq!(presets.summary(&Rules { num_players: 9, ..Rules::default() }), "Custom: 9 players, to 500 points");
+}
+
+#[test]
+fn presets_that_cant_be_played_get_the_default_rules()
+{
+    let text = r#"(presets: [(name: "Crowd", rules: (num_players: 40)), (name: "Rush", rules: (turn_time: 0)), (name: "Duel", rules: (num_players: 2))])"#;
+    let mut presets = RulePresets::from_ron(text).unwrap();
+    let reset = presets.reset_invalid();
+    assert_eq!(reset, vec![("Crowd".to_string(), RuleError::TooManyPlayers), ("Rush".to_string(), RuleError::NoTurnTime)]);
+    assert_eq!(presets.presets()[0].rules, Rules::default());
+    assert_eq!(presets.presets()[2].rules, Rules { num_players: 2, ..Rules::default() });
+}
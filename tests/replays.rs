@@ -0,0 +1,71 @@
+use dos_game::bots::{BotLevel, BotView};
+use dos_game::engine::*;
+use dos_game::replays::*;
+use rand::{rngs::StdRng, SeedableRng};
+
+// A round between Normal bots, dealt with `seed`
+fn played_round(seed: u64) -> GameEngine
+{
+    let rules = Rules { num_players: 3, last_call: false, ..Rules::default() };
+    let mut engine = GameEngine::new(rules, &mut StdRng::seed_from_u64(seed)).unwrap();
+    let mut rng = StdRng::seed_from_u64(7);
+    while engine.winner().is_none()
+    {
+        let seat = engine.turn();
+        let action = BotLevel::Normal.strategy().action(&BotView::new(&engine, seat), &mut rng);
+        engine.apply(action).unwrap();
+    }
+    engine
+}
+
+#[test]
+fn recorded_round_plays_back_to_the_same_table()
+{
+    let engine = played_round(11);
+    let replay = Replay::record(&engine, 11, 5, 1);
+    assert_eq!(replay.actions, engine.history());
+
+    let replayed = replay.table_after(replay.actions.len()).unwrap();
+    assert_eq!(replayed.winner(), engine.winner());
+    for seat in 0..engine.num_seats()
+    {
+        assert_eq!(replayed.hand(seat), engine.hand(seat));
+    }
+    assert_eq!(replayed.discard_pile(), engine.discard_pile());
+
+    // Stepping back deals again up to an earlier move
+    let dealt = replay.table_after(0).unwrap();
+    assert!(dealt.history().is_empty());
+    assert_eq!(dealt.hand(1), replay.deal().unwrap().hand(1));
+}
+
+#[test]
+fn replays_survive_a_round_trip_through_ron()
+{
+    let mut replays = Replays::default();
+    replays.push(Replay::record(&played_round(3), 3, 9, 2));
+    let text = replays.to_ron().unwrap();
+    assert_eq!(Replays::from_ron(&text).unwrap(), replays);
+}
+
+#[test]
+fn only_the_latest_replays_are_kept()
+{
+    let engine = played_round(1);
+    let mut replays = Replays::default();
+    for round in 0..MAX_REPLAYS + 3
+    {
+        replays.push(Replay::record(&engine, 1, 1, round));
+    }
+    assert_eq!(replays.replays().len(), MAX_REPLAYS);
+    assert_eq!(replays.replays()[0].round, 3);
+}
+
+#[test]
+fn action_that_does_not_follow_is_an_error()
+{
+    let engine = played_round(4);
+    let mut replay = Replay::record(&engine, 4, 4, 1);
+    replay.actions.insert(0, Action::Pass { seat: 0 });
+    assert!(matches!(replay.table_after(1), Err(ReplayError::Action(0, _))));
+}
@@ -0,0 +1,54 @@
+use dos_game::bots::BotLevel;
+use dos_game::simulation::*;
+
+fn options(args: &[&str]) -> Result<Options, String>
+{
+    parse_options(args.iter().map(|arg| arg.to_string()))
+}
+
+#[test]
+fn options_are_parsed()
+{
+    let options = options(&["--games", "3", "--bots", "nhE", "--think", "25", "--seed", "7", "--csv"]).unwrap();
+    assert_eq!(options.games, 3);
+    assert_eq!(options.bots, vec![BotLevel::Normal, BotLevel::Hard, BotLevel::Easy]);
+    assert_eq!(options.rules.num_players, 3);
+    assert_eq!(options.think.as_millis(), 25);
+    assert_eq!(options.seed, 7);
+    assert!(options.seeded);
+    assert!(options.csv);
+}
+
+#[test]
+fn players_without_bots_are_normal_bots()
+{
+    let options = options(&["--players", "4"]).unwrap();
+    assert_eq!(options.rules.num_players, 4);
+    assert_eq!(options.bots, vec![BotLevel::Normal; 4]);
+    assert!(!options.seeded);
+}
+
+#[test]
+fn bad_options_are_errors()
+{
+    assert_eq!(options(&["--players", "4", "--bots", "NN"]).unwrap_err(), "2 bots given for 4 players");
+    assert_eq!(options(&["--bots", "NX"]).unwrap_err(), "X is not a bot level");
+    assert_eq!(options(&["--games", "many"]).unwrap_err(), "many is not a number");
+    assert_eq!(options(&["--seed"]).unwrap_err(), "--seed needs a value");
+    assert_eq!(options(&["--fast"]).unwrap_err(), "Unknown option --fast");
+    assert!(options(&["--players", "40"]).is_err());
+}
+
+#[test]
+fn seeded_runs_end_the_same()
+{
+    let options = options(&["--games", "5", "--bots", "NHE", "--seed", "11"]).unwrap();
+    let first = simulate(&options);
+    let again = simulate(&options);
+
+    assert!(first.failed.is_empty());
+    assert_eq!(first.games, 5);
+    assert_eq!(first.wins.iter().sum::<usize>() + first.unfinished, 5);
+    assert_eq!(first.wins, again.wins);
+    assert_eq!(first.turns, again.turns);
+}